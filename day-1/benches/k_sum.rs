@@ -0,0 +1,56 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use day_1::ExpenseReport;
+use itertools::Itertools;
+
+fn generated_expense_report(len: usize) -> String {
+    (0..len)
+        .map(|i| ((i * 7919) % 2000 * 2).to_string())
+        .join("\n")
+}
+
+fn brute_force_find_entries_summing_to(
+    expenses: &[i64],
+    target: i64,
+    count: usize,
+) -> Option<Vec<i64>> {
+    expenses
+        .iter()
+        .copied()
+        .combinations(count)
+        .find(|expense_combo| expense_combo.iter().sum::<i64>() == target)
+}
+
+fn bench_find_entries_summing_to(c: &mut Criterion) {
+    // Every generated entry is even, so no combination of them adds up to an odd target, and
+    // every search has to run to completion even though the target is within reach.
+    let target = 2001;
+
+    for &count in &[2, 3] {
+        let mut group = c.benchmark_group(format!("find {} entries", count));
+        for &len in &[50, 100, 200, 2_000, 20_000] {
+            let report = generated_expense_report(len)
+                .parse::<ExpenseReport>()
+                .unwrap();
+
+            group.bench_with_input(BenchmarkId::new("k-sum", len), &report, |b, report| {
+                b.iter(|| report.find_entries_summing_to(target, count))
+            });
+            // Beyond a couple of hundred entries, trying every combination takes too long to
+            // measure
+            if len > 200 {
+                continue;
+            }
+            group.bench_with_input(
+                BenchmarkId::new("brute force combinations", len),
+                &report,
+                |b, report| {
+                    b.iter(|| brute_force_find_entries_summing_to(report.expenses(), target, count))
+                },
+            );
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_find_entries_summing_to);
+criterion_main!(benches);
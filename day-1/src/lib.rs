@@ -0,0 +1,1150 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::str::FromStr;
+use thiserror::Error;
+
+// Expenses with a fractional part are treated as money, so they're kept to the cent.
+pub const CURRENCY_DECIMAL_PLACES: u32 = 2;
+
+#[derive(Debug, PartialEq)]
+pub struct ExpenseReport {
+    // Each expense is scaled by 10^decimal_places, so that it can be matched exactly
+    expenses: Vec<i64>,
+    line_numbers: Vec<usize>,
+    decimal_places: u32,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Entry {
+    pub line_number: usize,
+    pub expense: i64,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ClosestEntries {
+    pub entries: Vec<Entry>,
+    // The sum of the entries minus the target
+    pub difference: i128,
+}
+
+impl ExpenseReport {
+    pub fn expenses(&self) -> &[i64] {
+        &self.expenses
+    }
+
+    pub fn decimal_places(&self) -> u32 {
+        self.decimal_places
+    }
+
+    pub fn parse_amount(&self, amount: &str) -> Result<i64, Day1Error> {
+        parse_fixed_point(amount, self.decimal_places).ok_or_else(|| Day1Error::InvalidAmount {
+            amount: amount.to_owned(),
+            decimal_places: self.decimal_places,
+        })
+    }
+
+    // Keeps every expense to `decimal_places` from now on, e.g. so that whole-number expenses can
+    // be compared with a target that has cents. Does nothing if the report already keeps at least
+    // that many.
+    pub fn rescale_to(&mut self, decimal_places: u32) -> Result<(), Day1Error> {
+        if decimal_places <= self.decimal_places {
+            return Ok(());
+        }
+
+        let rescale_overflow = || Day1Error::RescaleOverflow { decimal_places };
+        let scale = 10i64
+            .checked_pow(decimal_places - self.decimal_places)
+            .ok_or_else(rescale_overflow)?;
+        self.expenses = self
+            .expenses
+            .iter()
+            .map(|&expense| expense.checked_mul(scale))
+            .collect::<Option<_>>()
+            .ok_or_else(rescale_overflow)?;
+        self.decimal_places = decimal_places;
+        Ok(())
+    }
+
+    pub fn find_entries_summing_to(&self, target: i64, count: usize) -> Option<Vec<i64>> {
+        find_indices_summing_to(
+            &self.expenses,
+            &ExpenseIndex::new(&self.expenses),
+            0,
+            i128::from(target),
+            count,
+        )
+        .map(|indices| indices.into_iter().map(|i| self.expenses[i]).collect())
+    }
+
+    pub fn find_all_entries_summing_to(&self, target: i64, count: usize) -> Vec<Vec<Entry>> {
+        let mut all_indices = Vec::new();
+        find_all_indices_summing_to(
+            &self.expenses,
+            &ExpenseIndex::new(&self.expenses).indices_by_expense,
+            0,
+            i128::from(target),
+            count,
+            &mut Vec::with_capacity(count),
+            &mut all_indices,
+        );
+        all_indices
+            .into_iter()
+            .map(|indices| indices.into_iter().map(|i| self.entry(i)).collect())
+            .collect()
+    }
+
+    // Ties between sums that are just as near the target, whether above or below it, go to the
+    // entries with the lowest line numbers.
+    pub fn find_entries_closest_to(&self, target: i64, count: usize) -> Option<ClosestEntries> {
+        let mut sorted_expenses = self.expenses.clone();
+        sorted_expenses.sort_unstable();
+        let distance = closest_distance(&sorted_expenses, 0, i128::from(target), count)?;
+
+        let expense_index = ExpenseIndex::new(&self.expenses);
+        let (indices, difference) = [-distance, distance]
+            .iter()
+            .filter_map(|&difference| {
+                find_indices_summing_to(
+                    &self.expenses,
+                    &expense_index,
+                    0,
+                    i128::from(target) + difference,
+                    count,
+                )
+                .map(|indices| (indices, difference))
+            })
+            .min()?;
+        Some(ClosestEntries {
+            entries: indices.into_iter().map(|i| self.entry(i)).collect(),
+            difference,
+        })
+    }
+
+    fn entry(&self, index: usize) -> Entry {
+        Entry {
+            line_number: self.line_numbers[index],
+            expense: self.expenses[index],
+        }
+    }
+}
+
+// Lookups over a report's expenses that a search shares between all of its steps
+struct ExpenseIndex {
+    indices_by_expense: HashMap<i64, Vec<usize>>,
+    // Every distinct expense, smallest first
+    distinct_expenses: Vec<i64>,
+    // For each entry, the index of the closest entry before it with the same expense, if any
+    previous_index_of_same_expense: Vec<Option<usize>>,
+}
+
+impl ExpenseIndex {
+    fn new(expenses: &[i64]) -> ExpenseIndex {
+        let mut indices_by_expense: HashMap<i64, Vec<usize>> = HashMap::new();
+        let mut previous_index_of_same_expense = Vec::with_capacity(expenses.len());
+        for (index, &expense) in expenses.iter().enumerate() {
+            let indices = indices_by_expense.entry(expense).or_default();
+            previous_index_of_same_expense.push(indices.last().copied());
+            indices.push(index);
+        }
+        let mut distinct_expenses = indices_by_expense.keys().copied().collect::<Vec<_>>();
+        distinct_expenses.sort_unstable();
+        ExpenseIndex {
+            indices_by_expense,
+            distinct_expenses,
+            previous_index_of_same_expense,
+        }
+    }
+}
+
+// Returns the lexicographically smallest tuple of `count` ascending indices, all at or after
+// `start`, whose expenses add up to `target`. This is the same tuple that
+// `expenses[start..].iter().combinations(count).find(...)` would return first, but it only
+// enumerates the first `count - 2` entries and finds the last two from the sorted expenses.
+fn find_indices_summing_to(
+    expenses: &[i64],
+    expense_index: &ExpenseIndex,
+    start: usize,
+    target: i128,
+    count: usize,
+) -> Option<Vec<usize>> {
+    if expenses.len().saturating_sub(start) < count {
+        return None;
+    }
+
+    match count {
+        0 => Some(vec![]).filter(|_| target == 0),
+        1 => first_index_of(&expense_index.indices_by_expense, target, start)
+            .map(|index| vec![index]),
+        2 => first_pair_summing_to(expense_index, start, target)
+            .map(|(first, second)| vec![first, second]),
+        _ => {
+            // No `count` expenses can add up to less than `count` of the smallest one, or more
+            // than `count` of the largest one
+            let smallest = i128::from(*expense_index.distinct_expenses.first()?);
+            let largest = i128::from(*expense_index.distinct_expenses.last()?);
+            if target < smallest * count as i128 || target > largest * count as i128 {
+                return None;
+            }
+
+            for index in start..expenses.len() {
+                // If an earlier entry with the same value led to no match, this one can't either,
+                // because every entry after this one also came after that one.
+                if matches!(
+                    expense_index.previous_index_of_same_expense[index],
+                    Some(previous_index) if previous_index >= start
+                ) {
+                    continue;
+                }
+                if let Some(mut indices) = find_indices_summing_to(
+                    expenses,
+                    expense_index,
+                    index + 1,
+                    target - i128::from(expenses[index]),
+                    count - 1,
+                ) {
+                    indices.insert(0, index);
+                    return Some(indices);
+                }
+            }
+            None
+        }
+    }
+}
+
+// Returns the lexicographically smallest pair of ascending indices, both at or after `start`,
+// whose expenses add up to `target`. Each pair of distinct expenses that adds up to `target` is
+// found by closing in on it from both ends of the sorted expenses, and then placed at the first
+// entries of those expenses at or after `start`.
+fn first_pair_summing_to(
+    expense_index: &ExpenseIndex,
+    start: usize,
+    target: i128,
+) -> Option<(usize, usize)> {
+    let distinct_expenses = &expense_index.distinct_expenses;
+    let indices_from = |expense: i64| {
+        indices_of(
+            &expense_index.indices_by_expense,
+            i128::from(expense),
+            start,
+        )
+    };
+
+    let mut first_pair: Option<(usize, usize)> = None;
+    let mut low = 0;
+    let mut high = distinct_expenses.len().checked_sub(1)?;
+    while low <= high {
+        let (low_expense, high_expense) = (distinct_expenses[low], distinct_expenses[high]);
+        let sum = i128::from(low_expense) + i128::from(high_expense);
+        if sum < target {
+            low += 1;
+            continue;
+        }
+        if sum == target {
+            let pair = if low == high {
+                let mut indices = indices_from(low_expense);
+                indices.next().zip(indices.next())
+            } else {
+                match (
+                    indices_from(low_expense).next(),
+                    indices_from(high_expense).next(),
+                ) {
+                    (Some(a), Some(b)) => Some((a.min(b), a.max(b))),
+                    _ => None,
+                }
+            };
+            first_pair = match (first_pair, pair) {
+                (Some(first_pair), Some(pair)) => Some(first_pair.min(pair)),
+                (first_pair, pair) => first_pair.or(pair),
+            };
+            low += 1;
+        }
+        if high == 0 {
+            break;
+        }
+        high -= 1;
+    }
+    first_pair
+}
+
+// Collects every tuple of `count` ascending indices, all at or after `start`, whose expenses add
+// up to `target`, in lexicographic order. Entries with equal expenses on different lines are
+// distinct, so their tuples are collected separately.
+fn find_all_indices_summing_to(
+    expenses: &[i64],
+    indices_by_expense: &HashMap<i64, Vec<usize>>,
+    start: usize,
+    target: i128,
+    count: usize,
+    prefix: &mut Vec<usize>,
+    all_indices: &mut Vec<Vec<usize>>,
+) {
+    if expenses.len().saturating_sub(start) < count {
+        return;
+    }
+
+    match count {
+        0 => {
+            if target == 0 {
+                all_indices.push(prefix.clone());
+            }
+        }
+        1 => {
+            for index in indices_of(indices_by_expense, target, start) {
+                prefix.push(index);
+                all_indices.push(prefix.clone());
+                prefix.pop();
+            }
+        }
+        _ => {
+            for index in start..expenses.len() {
+                prefix.push(index);
+                find_all_indices_summing_to(
+                    expenses,
+                    indices_by_expense,
+                    index + 1,
+                    target - i128::from(expenses[index]),
+                    count - 1,
+                    prefix,
+                    all_indices,
+                );
+                prefix.pop();
+            }
+        }
+    }
+}
+
+// Finds how near to `target` any `count` of `sorted_expenses[start..]` can add up to, or `None` if
+// there are fewer than `count` of them. All but the last two expenses are tried in turn, and the
+// last two are found by closing in on `target` from both ends of the rest.
+fn closest_distance(
+    sorted_expenses: &[i64],
+    start: usize,
+    target: i128,
+    count: usize,
+) -> Option<i128> {
+    if sorted_expenses.len().saturating_sub(start) < count {
+        return None;
+    }
+
+    let distance_to = |sum: i128| (sum - target).abs();
+    match count {
+        0 => Some(distance_to(0)),
+        1 => sorted_expenses[start..]
+            .iter()
+            .map(|&expense| distance_to(i128::from(expense)))
+            .min(),
+        2 => {
+            let mut closest = None;
+            let (mut low, mut high) = (start, sorted_expenses.len() - 1);
+            while low < high {
+                let sum = i128::from(sorted_expenses[low]) + i128::from(sorted_expenses[high]);
+                closest = closest.into_iter().chain(Some(distance_to(sum))).min();
+                if sum < target {
+                    low += 1;
+                } else if sum > target {
+                    high -= 1;
+                } else {
+                    break;
+                }
+            }
+            closest
+        }
+        _ => {
+            let mut closest: Option<i128> = None;
+            for index in start..sorted_expenses.len() {
+                // Starting from the same expense again can't get any nearer
+                if index > start && sorted_expenses[index] == sorted_expenses[index - 1] {
+                    continue;
+                }
+                let distance = closest_distance(
+                    sorted_expenses,
+                    index + 1,
+                    target - i128::from(sorted_expenses[index]),
+                    count - 1,
+                );
+                closest = closest.into_iter().chain(distance).min();
+                if closest == Some(0) {
+                    break;
+                }
+            }
+            closest
+        }
+    }
+}
+
+fn first_index_of(
+    indices_by_expense: &HashMap<i64, Vec<usize>>,
+    expense: i128,
+    start: usize,
+) -> Option<usize> {
+    indices_of(indices_by_expense, expense, start).next()
+}
+
+fn indices_of(
+    indices_by_expense: &HashMap<i64, Vec<usize>>,
+    expense: i128,
+    start: usize,
+) -> impl Iterator<Item = usize> + '_ {
+    let indices = i64::try_from(expense)
+        .ok()
+        .and_then(|expense| indices_by_expense.get(&expense))
+        .map(|indices| indices.as_slice())
+        .unwrap_or(&[]);
+    let position = match indices.binary_search(&start) {
+        Ok(position) | Err(position) => position,
+    };
+    indices[position..].iter().copied()
+}
+
+pub fn checked_product(expenses: &[i64]) -> Result<i128, Day1Error> {
+    expenses
+        .iter()
+        .try_fold(1i128, |product, &expense| {
+            product.checked_mul(i128::from(expense))
+        })
+        .ok_or_else(|| Day1Error::ProductOverflow {
+            expenses: expenses.to_vec(),
+        })
+}
+
+// Formats a value that is scaled by 10^decimal_places, e.g. 1250 to 2 decimal places is "12.50".
+pub fn format_fixed_point(value: i128, decimal_places: u32) -> String {
+    let digits = value.to_string();
+    let (sign, digits) = match digits.strip_prefix('-') {
+        Some(digits) => ("-", digits),
+        None => ("", digits.as_str()),
+    };
+    if decimal_places == 0 {
+        return format!("{}{}", sign, digits);
+    }
+
+    let decimal_places = decimal_places as usize;
+    let digits = format!("{:0>width$}", digits, width = decimal_places + 1);
+    let (whole, fraction) = digits.split_at(digits.len() - decimal_places);
+    format!("{}{}.{}", sign, whole, fraction)
+}
+
+// Parses a whole number or a decimal number into a value scaled by 10^decimal_places, e.g.
+// "-3.07" to 2 decimal places is -307. Trailing zeros past decimal_places are allowed, but any
+// other digits there are rejected rather than rounded.
+fn parse_fixed_point(s: &str, decimal_places: u32) -> Option<i64> {
+    let (is_negative, unsigned) = match s.strip_prefix('-') {
+        Some(unsigned) => (true, unsigned),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let (whole, fraction) = match unsigned.find('.') {
+        Some(index) => (&unsigned[..index], &unsigned[index + 1..]),
+        None => (unsigned, "0"),
+    };
+    let is_digits = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+    if !is_digits(whole) || !is_digits(fraction) {
+        return None;
+    }
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.len() > decimal_places as usize {
+        return None;
+    }
+
+    let scale = 10i128.pow(decimal_places);
+    let whole = whole.parse::<i128>().ok()?.checked_mul(scale)?;
+    let fraction = if fraction.is_empty() {
+        0
+    } else {
+        fraction.parse::<i128>().ok()? * 10i128.pow(decimal_places - fraction.len() as u32)
+    };
+    let magnitude = whole.checked_add(fraction)?;
+    i64::try_from(if is_negative { -magnitude } else { magnitude }).ok()
+}
+
+impl FromStr for ExpenseReport {
+    type Err = Day1Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let decimal_places = if s.contains('.') {
+            CURRENCY_DECIMAL_PLACES
+        } else {
+            0
+        };
+
+        let mut expenses = Vec::new();
+        let mut line_numbers = Vec::new();
+        for (index, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if !line.is_empty() {
+                expenses.push(parse_fixed_point(line, decimal_places).ok_or_else(|| {
+                    Day1Error::InvalidExpense {
+                        index,
+                        line: line.to_owned(),
+                    }
+                })?);
+                line_numbers.push(index + 1);
+            }
+        }
+        Ok(ExpenseReport {
+            expenses,
+            line_numbers,
+            decimal_places,
+        })
+    }
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum Day1Error {
+    #[error(
+        "Line {index} is not a whole number or an amount with at most {} decimal places: \"{line}\"",
+        CURRENCY_DECIMAL_PLACES
+    )]
+    InvalidExpense { index: usize, line: String },
+    #[error("\"{amount}\" is not a number with at most {decimal_places} decimal places.")]
+    InvalidAmount { amount: String, decimal_places: u32 },
+    #[error("The expenses are too large to be kept to {decimal_places} decimal places.")]
+    RescaleOverflow { decimal_places: u32 },
+    #[error("The product of expenses {expenses:?} is too large to be represented.")]
+    ProductOverflow { expenses: Vec<i64> },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    mod given_empty_expense_report {
+        use super::*;
+
+        mod when_finding_two_entries_summing_to_2020 {
+            use super::*;
+
+            #[test]
+            fn then_it_finds_nothing() -> Result<(), Box<dyn std::error::Error>> {
+                let report = "".parse::<ExpenseReport>()?;
+
+                let result = report.find_entries_summing_to(2020, 2);
+
+                assert_eq!(result, None);
+
+                Ok(())
+            }
+        }
+    }
+
+    mod given_expense_report_with_non_numeric_line {
+        use super::*;
+
+        mod when_parsing_it {
+            use super::*;
+
+            #[test]
+            fn then_it_returns_an_error() {
+                let result = indoc! { "
+                    1721
+                    abc
+                " }
+                .parse::<ExpenseReport>();
+
+                assert_eq!(
+                    result,
+                    Err(Day1Error::InvalidExpense {
+                        index: 1,
+                        line: "abc".to_owned()
+                    })
+                );
+            }
+        }
+    }
+
+    mod given_expense_report_with_duplicate_entries {
+        use super::*;
+
+        mod when_finding_three_entries_summing_to_2020 {
+            use super::*;
+
+            #[test]
+            fn then_it_can_use_each_duplicate_once() -> Result<(), Box<dyn std::error::Error>> {
+                let report = indoc! { "
+                    1010
+                    5
+                    1010
+                    0
+                " }
+                .parse::<ExpenseReport>()?;
+
+                let result = report.find_entries_summing_to(2020, 3);
+
+                assert_eq!(result, Some(vec![1010, 1010, 0]));
+
+                Ok(())
+            }
+        }
+
+        mod when_finding_all_pairs_of_entries_summing_to_1015 {
+            use super::*;
+
+            #[test]
+            fn then_it_reports_each_duplicate_separately() -> Result<(), Box<dyn std::error::Error>>
+            {
+                let report = indoc! { "
+                    1010
+                    5
+                    1010
+                    0
+                " }
+                .parse::<ExpenseReport>()?;
+
+                let result = report.find_all_entries_summing_to(1015, 2);
+
+                assert_eq!(
+                    result,
+                    vec![
+                        vec![
+                            Entry {
+                                line_number: 1,
+                                expense: 1010
+                            },
+                            Entry {
+                                line_number: 2,
+                                expense: 5
+                            }
+                        ],
+                        vec![
+                            Entry {
+                                line_number: 2,
+                                expense: 5
+                            },
+                            Entry {
+                                line_number: 3,
+                                expense: 1010
+                            }
+                        ],
+                    ]
+                );
+
+                Ok(())
+            }
+        }
+    }
+
+    mod given_expense_report_with_entries_near_i64_max {
+        use super::*;
+        use indoc::formatdoc;
+
+        mod when_finding_three_entries_summing_to_i64_max {
+            use super::*;
+
+            #[test]
+            fn then_it_finds_entries_whose_partial_sums_overflow_i64(
+            ) -> Result<(), Box<dyn std::error::Error>> {
+                let report = formatdoc! { "
+                    -1
+                    {}
+                    1
+                ", i64::MAX }
+                .parse::<ExpenseReport>()?;
+
+                let result = report.find_entries_summing_to(i64::MAX, 3);
+
+                assert_eq!(result, Some(vec![-1, i64::MAX, 1]));
+
+                Ok(())
+            }
+        }
+
+        mod when_finding_two_entries_whose_wrapped_sum_is_the_target {
+            use super::*;
+
+            #[test]
+            fn then_it_finds_nothing() -> Result<(), Box<dyn std::error::Error>> {
+                let report = formatdoc! { "
+                    {max}
+                    {max}
+                ", max = i64::MAX }
+                .parse::<ExpenseReport>()?;
+
+                let result = report.find_entries_summing_to(-2, 2);
+                let all_results = report.find_all_entries_summing_to(-2, 2);
+
+                assert_eq!(result, None);
+                assert!(all_results.is_empty());
+
+                Ok(())
+            }
+        }
+
+        mod when_multiplying_two_of_them {
+            use super::*;
+
+            #[test]
+            fn then_it_returns_the_exact_product() {
+                let result = checked_product(&[i64::MAX, 2]);
+
+                assert_eq!(result, Ok(i128::from(i64::MAX) * 2));
+            }
+        }
+
+        mod when_multiplying_three_of_them {
+            use super::*;
+
+            #[test]
+            fn then_it_returns_an_error() {
+                let result = checked_product(&[i64::MAX, i64::MAX, i64::MAX]);
+
+                assert_eq!(
+                    result,
+                    Err(Day1Error::ProductOverflow {
+                        expenses: vec![i64::MAX, i64::MAX, i64::MAX]
+                    })
+                );
+            }
+        }
+    }
+
+    mod given_generated_expense_reports {
+        use super::*;
+        use itertools::Itertools;
+        use test_case::test_case;
+
+        fn generated_expense_report(len: usize, seed: i64) -> ExpenseReport {
+            let expenses = (0..len as i64)
+                .map(|i| (i * 7919 + seed * 104_729) % 1000 - 300)
+                .collect();
+            let line_numbers = (1..=len).collect();
+            ExpenseReport {
+                expenses,
+                line_numbers,
+                decimal_places: 0,
+            }
+        }
+
+        #[test_case(1 ; "when finding one entry")]
+        #[test_case(2 ; "when finding two entries")]
+        #[test_case(3 ; "when finding three entries")]
+        #[test_case(4 ; "when finding four entries")]
+        fn then_it_finds_same_first_match_as_brute_force_search(count: usize) {
+            for seed in 0..5 {
+                let report = generated_expense_report(20, seed);
+                for target in -50..50 {
+                    let expected = report
+                        .expenses()
+                        .iter()
+                        .copied()
+                        .combinations(count)
+                        .find(|expense_combo| expense_combo.iter().sum::<i64>() == target);
+
+                    let result = report.find_entries_summing_to(target, count);
+
+                    assert_eq!(result, expected, "seed {}, target {}", seed, target);
+                }
+            }
+        }
+
+        #[test_case(2 ; "when finding two entries among many duplicates")]
+        #[test_case(3 ; "when finding three entries among many duplicates")]
+        fn then_it_finds_same_first_match_among_duplicates_as_brute_force_search(count: usize) {
+            for seed in 0..5 {
+                let mut report = generated_expense_report(20, seed);
+                for expense in report.expenses.iter_mut() {
+                    *expense = expense.rem_euclid(7) - 3;
+                }
+                for target in -10..10 {
+                    let expected = (0..report.expenses.len())
+                        .combinations(count)
+                        .find(|indices| {
+                            indices.iter().map(|&i| report.expenses[i]).sum::<i64>() == target
+                        });
+
+                    let result = find_indices_summing_to(
+                        &report.expenses,
+                        &ExpenseIndex::new(&report.expenses),
+                        0,
+                        i128::from(target),
+                        count,
+                    );
+
+                    assert_eq!(result, expected, "seed {}, target {}", seed, target);
+                }
+            }
+        }
+
+        #[test_case(1 ; "when finding the nearest entry")]
+        #[test_case(2 ; "when finding the nearest two entries")]
+        #[test_case(3 ; "when finding the nearest three entries")]
+        fn then_it_finds_same_closest_entries_as_brute_force_search(count: usize) {
+            for seed in 0..5 {
+                let report = generated_expense_report(20, seed);
+                for target in (-1500..2500).step_by(37) {
+                    let expected = (0..report.expenses.len())
+                        .combinations(count)
+                        .map(|indices| {
+                            let sum = indices.iter().map(|&i| report.expenses[i]).sum::<i64>();
+                            (i128::from(sum - target), indices)
+                        })
+                        .min_by_key(|(difference, _)| difference.abs())
+                        .map(|(difference, indices)| ClosestEntries {
+                            entries: indices.into_iter().map(|i| report.entry(i)).collect(),
+                            difference,
+                        });
+
+                    let result = report.find_entries_closest_to(target, count);
+
+                    assert_eq!(result, expected, "seed {}, target {}", seed, target);
+                }
+            }
+        }
+
+        #[test_case(1 ; "when finding all single entries")]
+        #[test_case(2 ; "when finding all pairs of entries")]
+        #[test_case(3 ; "when finding all triples of entries")]
+        fn then_it_finds_same_matches_as_brute_force_search(count: usize) {
+            for seed in 0..5 {
+                let report = generated_expense_report(20, seed);
+                for target in -50..50 {
+                    let expected = (0..report.expenses.len())
+                        .combinations(count)
+                        .filter(|indices| {
+                            indices.iter().map(|&i| report.expenses[i]).sum::<i64>() == target
+                        })
+                        .map(|indices| indices.into_iter().map(|i| report.entry(i)).collect_vec())
+                        .collect_vec();
+
+                    let result = report.find_all_entries_summing_to(target, count);
+
+                    assert_eq!(result, expected, "seed {}, target {}", seed, target);
+                }
+            }
+        }
+    }
+
+    mod given_expense_report_with_no_exact_match {
+        use super::*;
+
+        mod when_finding_two_entries_closest_to_2020 {
+            use super::*;
+
+            #[test]
+            fn then_it_breaks_ties_by_lowest_line_numbers() -> Result<(), Box<dyn std::error::Error>>
+            {
+                let report = indoc! { "
+                    1000
+                    1030
+
+                    1000
+                    1010
+                " }
+                .parse::<ExpenseReport>()?;
+
+                let result = report.find_entries_closest_to(2020, 2);
+
+                assert_eq!(
+                    result,
+                    Some(ClosestEntries {
+                        entries: vec![
+                            Entry {
+                                line_number: 1,
+                                expense: 1000
+                            },
+                            Entry {
+                                line_number: 2,
+                                expense: 1030
+                            }
+                        ],
+                        difference: 10,
+                    })
+                );
+
+                Ok(())
+            }
+        }
+
+        mod when_finding_more_entries_than_it_has {
+            use super::*;
+
+            #[test]
+            fn then_it_finds_nothing() -> Result<(), Box<dyn std::error::Error>> {
+                let report = "1000\n1030".parse::<ExpenseReport>()?;
+
+                let result = report.find_entries_closest_to(2020, 3);
+
+                assert_eq!(result, None);
+
+                Ok(())
+            }
+        }
+    }
+
+    mod given_expense_report_with_monetary_amounts {
+        use super::*;
+
+        const MONETARY_EXPENSE_REPORT: &str = indoc! { "
+            12.50
+            -3.07
+            7
+            0.5
+        " };
+
+        mod when_parsing_it {
+            use super::*;
+
+            #[test]
+            fn then_it_keeps_every_expense_to_the_cent() -> Result<(), Box<dyn std::error::Error>> {
+                let report = MONETARY_EXPENSE_REPORT.parse::<ExpenseReport>()?;
+
+                assert_eq!(report.decimal_places(), 2);
+                assert_eq!(report.expenses(), &[1250, -307, 700, 50]);
+
+                Ok(())
+            }
+        }
+
+        mod when_finding_two_entries_summing_to_a_decimal_target {
+            use super::*;
+
+            #[test]
+            fn then_it_matches_exactly() -> Result<(), Box<dyn std::error::Error>> {
+                let report = MONETARY_EXPENSE_REPORT.parse::<ExpenseReport>()?;
+                let target = report.parse_amount("9.43")?;
+
+                let result = report.find_entries_summing_to(target, 2);
+
+                assert_eq!(result, Some(vec![1250, -307]));
+
+                Ok(())
+            }
+        }
+
+        mod when_parsing_a_target_with_too_many_decimal_places {
+            use super::*;
+
+            #[test]
+            fn then_it_returns_an_error() -> Result<(), Box<dyn std::error::Error>> {
+                let report = MONETARY_EXPENSE_REPORT.parse::<ExpenseReport>()?;
+
+                let result = report.parse_amount("9.435");
+
+                assert_eq!(
+                    result,
+                    Err(Day1Error::InvalidAmount {
+                        amount: "9.435".to_owned(),
+                        decimal_places: 2
+                    })
+                );
+
+                Ok(())
+            }
+        }
+    }
+
+    mod given_expense_report_of_whole_numbers {
+        use super::*;
+
+        mod when_parsing_a_decimal_target {
+            use super::*;
+
+            #[test]
+            fn then_it_returns_an_error() -> Result<(), Box<dyn std::error::Error>> {
+                let report = "1000\n7\n1007".parse::<ExpenseReport>()?;
+
+                let result = report.parse_amount("1007.50");
+
+                assert_eq!(
+                    result,
+                    Err(Day1Error::InvalidAmount {
+                        amount: "1007.50".to_owned(),
+                        decimal_places: 0
+                    })
+                );
+
+                Ok(())
+            }
+        }
+
+        mod when_rescaling_it_to_the_cent {
+            use super::*;
+
+            #[test]
+            fn then_it_keeps_every_expense_to_the_cent() -> Result<(), Box<dyn std::error::Error>> {
+                let mut report = "1000\n7\n1007".parse::<ExpenseReport>()?;
+
+                report.rescale_to(CURRENCY_DECIMAL_PLACES)?;
+
+                assert_eq!(report.decimal_places(), 2);
+                assert_eq!(report.expenses(), &[100_000, 700, 100_700]);
+
+                Ok(())
+            }
+
+            #[test]
+            fn then_it_matches_decimal_targets_exactly() -> Result<(), Box<dyn std::error::Error>> {
+                let mut report = "1000\n7\n1007".parse::<ExpenseReport>()?;
+                report.rescale_to(CURRENCY_DECIMAL_PLACES)?;
+
+                let inexact_result =
+                    report.find_entries_summing_to(report.parse_amount("1007.50")?, 2);
+                let exact_result =
+                    report.find_entries_summing_to(report.parse_amount("1007.00")?, 2);
+
+                assert_eq!(inexact_result, None);
+                assert_eq!(exact_result, Some(vec![100_000, 700]));
+
+                Ok(())
+            }
+
+            #[test]
+            fn then_it_returns_an_error_if_an_expense_is_too_large(
+            ) -> Result<(), Box<dyn std::error::Error>> {
+                let mut report = "9223372036854775807".parse::<ExpenseReport>()?;
+
+                let result = report.rescale_to(CURRENCY_DECIMAL_PLACES);
+
+                assert_eq!(
+                    result,
+                    Err(Day1Error::RescaleOverflow { decimal_places: 2 })
+                );
+                assert_eq!(report.expenses(), &[9_223_372_036_854_775_807]);
+
+                Ok(())
+            }
+        }
+    }
+
+    mod given_expense_report_with_too_many_decimal_places {
+        use super::*;
+
+        mod when_parsing_it {
+            use super::*;
+
+            #[test]
+            fn then_it_returns_an_error() {
+                let result = "12.50\n1.005".parse::<ExpenseReport>();
+
+                assert_eq!(
+                    result,
+                    Err(Day1Error::InvalidExpense {
+                        index: 1,
+                        line: "1.005".to_owned()
+                    })
+                );
+            }
+        }
+    }
+
+    mod given_expense_report_with_an_amount_too_large_to_represent {
+        use super::*;
+
+        mod when_parsing_it {
+            use super::*;
+
+            #[test]
+            fn then_it_returns_an_error() {
+                let result = "1701411834604692317316873037158841057.99".parse::<ExpenseReport>();
+
+                assert_eq!(
+                    result,
+                    Err(Day1Error::InvalidExpense {
+                        index: 0,
+                        line: "1701411834604692317316873037158841057.99".to_owned()
+                    })
+                );
+            }
+        }
+    }
+
+    mod given_fixed_point_values {
+        use super::*;
+        use test_case::test_case;
+
+        #[test_case(514579, 0, "514579" ; "with no decimal places")]
+        #[test_case(1250, 2, "12.50" ; "with 2 decimal places")]
+        #[test_case(-307, 2, "-3.07" ; "when negative")]
+        #[test_case(5, 4, "0.0005" ; "when smaller than 1")]
+        #[test_case(-5, 2, "-0.05" ; "when negative and greater than -1")]
+        fn then_they_are_formatted_exactly(value: i128, decimal_places: u32, expected: &str) {
+            assert_eq!(format_fixed_point(value, decimal_places), expected);
+        }
+    }
+
+    mod given_example_expense_report {
+        use super::*;
+
+        const EXAMPLE_EXPENSE_REPORT: &str = indoc! { "
+            1721
+            979
+            366
+            299
+            675
+            1456
+        " };
+
+        mod when_parsing_it {
+            use super::*;
+
+            #[test]
+            fn then_it_skips_blank_lines() -> Result<(), Box<dyn std::error::Error>> {
+                let report = EXAMPLE_EXPENSE_REPORT.parse::<ExpenseReport>()?;
+
+                assert_eq!(report.expenses(), &[1721, 979, 366, 299, 675, 1456]);
+                assert_eq!(report.decimal_places(), 0);
+
+                Ok(())
+            }
+        }
+
+        mod when_finding_two_entries_summing_to_2020 {
+            use super::*;
+
+            #[test]
+            fn then_it_finds_1721_and_299() -> Result<(), Box<dyn std::error::Error>> {
+                let report = EXAMPLE_EXPENSE_REPORT.parse::<ExpenseReport>()?;
+
+                let result = report.find_entries_summing_to(2020, 2);
+
+                assert_eq!(result, Some(vec![1721, 299]));
+
+                Ok(())
+            }
+        }
+
+        mod when_finding_two_entries_closest_to_2020 {
+            use super::*;
+
+            #[test]
+            fn then_it_finds_the_exact_match() -> Result<(), Box<dyn std::error::Error>> {
+                let report = EXAMPLE_EXPENSE_REPORT.parse::<ExpenseReport>()?;
+
+                let result = report.find_entries_closest_to(2020, 2);
+
+                assert_eq!(
+                    result,
+                    Some(ClosestEntries {
+                        entries: vec![
+                            Entry {
+                                line_number: 1,
+                                expense: 1721
+                            },
+                            Entry {
+                                line_number: 4,
+                                expense: 299
+                            }
+                        ],
+                        difference: 0,
+                    })
+                );
+
+                Ok(())
+            }
+        }
+
+        mod when_finding_three_entries_summing_to_2020 {
+            use super::*;
+
+            #[test]
+            fn then_it_finds_979_366_and_675() -> Result<(), Box<dyn std::error::Error>> {
+                let report = EXAMPLE_EXPENSE_REPORT.parse::<ExpenseReport>()?;
+
+                let result = report.find_entries_summing_to(2020, 3);
+
+                assert_eq!(result, Some(vec![979, 366, 675]));
+
+                Ok(())
+            }
+        }
+    }
+}
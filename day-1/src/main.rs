@@ -1,72 +1,135 @@
+use anyhow::{anyhow, Context, Result};
+use clap::{clap_app, crate_authors, crate_description, crate_name, crate_version};
+use day_1::{checked_product, format_fixed_point, ExpenseReport, CURRENCY_DECIMAL_PLACES};
 use itertools::Itertools;
-use std::{env::args, fs::File, io, io::BufRead, path::Path};
+use std::fs::read_to_string;
+use std::path::PathBuf;
 
-fn main() {
-    std::process::exit(match run_app() {
-        Ok(_) => 0,
-        Err(err) => {
-            eprintln!("{}", err);
-            1
-        }
-    });
-}
+fn main() -> Result<()> {
+    let matches = clap_app!(app =>
+        (name: crate_name!())
+        (version: crate_version!())
+        (author: crate_authors!())
+        (about: crate_description!())
+        (@arg INPUT: +required "Sets the expense report file to use")
+        (@arg COUNT: -c --count +takes_value default_value("2")
+            "How many expenses should add up to the target")
+        (@arg TARGET: -t --target +takes_value default_value("2020")
+            "What the expenses should add up to. May have up to two decimal places.")
+        (@arg ALL: --all conflicts_with[CLOSEST]
+            "Should we list every combination of expenses that adds up to the target, not just \
+             the product of the first one?")
+        (@arg CLOSEST: --closest
+            "Should we find the combination of expenses whose sum is nearest the target, even if \
+             none add up to it exactly?")
+    )
+    .get_matches();
 
-fn run_app() -> Result<(), String> {
-    let expense_report_file = args()
-        .nth(1)
-        .ok_or("Please pass in a file name pointing to the expense report as the 1st argument.")?;
-    let num_expenses_to_find = args()
-        .nth(2)
-        .ok_or("Please pass in a number for how many expenses should add up to 2020 as the 2nd argument.")?
+    let expense_report_file = matches.value_of("INPUT").map(PathBuf::from).unwrap();
+    let target = matches.value_of("TARGET").unwrap();
+    let num_expenses_to_find = matches
+        .value_of("COUNT")
+        .unwrap()
         .parse::<usize>()
-        .map_err(|_| "The value for how many expenses should add up to 2020 is not a whole non-negative number.")?;
+        .map_err(|_| {
+            anyhow!(
+                "The value for how many expenses should add up to {} is not a whole non-negative number.",
+                target
+            )
+        })?;
+    let find_all = matches.is_present("ALL");
+    let find_closest = matches.is_present("CLOSEST");
 
-    let expense_report_lines = match read_lines(&expense_report_file) {
-        Ok(expense_report_lines) => expense_report_lines,
-        Err(_) => return Err(format!(r#"File "{}" cannot be found."#, expense_report_file)),
+    let mut expense_report = read_to_string(&expense_report_file)
+        .with_context(|| {
+            format!(
+                r#"File "{}" cannot be found."#,
+                expense_report_file.display()
+            )
+        })?
+        .parse::<ExpenseReport>()?;
+    // A target with cents can still be matched against whole-number expenses once they're kept to
+    // the cent too
+    if expense_report.parse_amount(target).is_err() {
+        expense_report.rescale_to(CURRENCY_DECIMAL_PLACES)?;
+    }
+    let target_amount = expense_report
+        .parse_amount(target)
+        .context("The value for what the expenses should add up to is not a valid amount.")?;
+    let decimal_places = expense_report.decimal_places();
+    let product_of = |expenses: &[i64]| -> Result<String> {
+        Ok(format_fixed_point(
+            checked_product(expenses)?,
+            decimal_places * expenses.len() as u32,
+        ))
+    };
+    let format_expenses = |expenses: &[i64], separator: &str| {
+        expenses
+            .iter()
+            .map(|&e| format_fixed_point(i128::from(e), decimal_places))
+            .join(separator)
     };
 
-    let mut expenses = Vec::new();
-    for (index, line) in expense_report_lines.enumerate() {
-        let line = match line {
-            Ok(line) => line,
-            Err(_) => return Err(format!("Line {} cannot be read.", index)),
-        };
+    let no_expenses_add_up_to_target = || {
+        anyhow!(
+            r#"There are no expenses in "{}" that add up to {}."#,
+            expense_report_file.display(),
+            target
+        )
+    };
 
-        let line = line.trim().to_string();
-        if !line.is_empty() {
-            expenses.push(match line.parse::<i64>() {
-                Ok(expense) => expense,
-                Err(_) => {
-                    return Err(format!(
-                        r#"Line {} is not a whole number: "{}""#,
-                        index, line
-                    ))
-                }
-            });
+    if find_all {
+        let all_wanted_entries =
+            expense_report.find_all_entries_summing_to(target_amount, num_expenses_to_find);
+        if all_wanted_entries.is_empty() {
+            return Err(no_expenses_add_up_to_target());
         }
-    }
-
-    let wanted_expenses = expenses
-        .iter()
-        .combinations(num_expenses_to_find)
-        .find(|expense_combo| expense_combo.iter().map(|&e| e).sum::<i64>() == 2020);
-
-    return if let Some(wanted_expenses) = wanted_expenses {
-        println!("{:?}", wanted_expenses.iter().map(|&e| e).product::<i64>());
-        Ok(())
+        for wanted_entries in all_wanted_entries {
+            let wanted_expenses = wanted_entries.iter().map(|e| e.expense).collect_vec();
+            println!(
+                "Lines {}: {} = {}, product {}",
+                wanted_entries.iter().map(|e| e.line_number).join(", "),
+                format_expenses(&wanted_expenses, " + "),
+                format_fixed_point(i128::from(target_amount), decimal_places),
+                product_of(&wanted_expenses)?
+            );
+        }
+    } else if find_closest {
+        let closest_entries = expense_report
+            .find_entries_closest_to(target_amount, num_expenses_to_find)
+            .ok_or_else(|| {
+                anyhow!(
+                    r#"There are fewer than {} expenses in "{}"."#,
+                    num_expenses_to_find,
+                    expense_report_file.display()
+                )
+            })?;
+        let closest_expenses = closest_entries
+            .entries
+            .iter()
+            .map(|e| e.expense)
+            .collect_vec();
+        let difference = closest_entries.difference;
+        println!(
+            "Lines {}: {} = {}, difference {}{}, product {}",
+            closest_entries
+                .entries
+                .iter()
+                .map(|e| e.line_number)
+                .join(", "),
+            format_expenses(&closest_expenses, " + "),
+            format_fixed_point(i128::from(target_amount) + difference, decimal_places),
+            if difference >= 0 { "+" } else { "" },
+            format_fixed_point(difference, decimal_places),
+            product_of(&closest_expenses)?
+        );
     } else {
-        Err(format!(
-            r#"There are no expenses in "{}" that add up to 2020."#,
-            expense_report_file
-        ))
-    };
-}
+        let wanted_expenses = expense_report
+            .find_entries_summing_to(target_amount, num_expenses_to_find)
+            .ok_or_else(no_expenses_add_up_to_target)?;
+
+        println!("{}", product_of(&wanted_expenses)?);
+    }
 
-fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
-where
-    P: AsRef<Path>,
-{
-    let file = File::open(filename)?;
-    Ok(io::BufReader::new(file).lines())
+    Ok(())
 }
@@ -1,3 +1,46 @@
+#[cfg(test)]
+mod when_not_given_an_expense_report {
+
+    use assert_cmd::prelude::*;
+    use predicates::prelude::*;
+    use std::process::Command;
+
+    #[test]
+    fn then_prints_usage() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("day-1")?;
+
+        cmd.assert()
+            .failure()
+            .stderr(predicate::str::contains("USAGE:"))
+            .stderr(predicate::str::contains("<INPUT>"));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod when_asked_for_help {
+
+    use assert_cmd::prelude::*;
+    use predicates::prelude::*;
+    use std::process::Command;
+
+    #[test]
+    fn then_prints_help_with_every_option() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("day-1")?;
+        cmd.arg("--help");
+
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains("USAGE:"))
+            .stdout(predicate::str::contains("--count <COUNT>"))
+            .stdout(predicate::str::contains("--target <TARGET>"))
+            .stdout(predicate::str::contains("--all"))
+            .stdout(predicate::str::contains("--closest"))
+            .stdout(predicate::str::contains("--version"));
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod given_expense_report {
     mod when_searching_for_two_expenses_that_sum_to_2020 {
@@ -26,7 +69,9 @@ mod given_expense_report {
             let expected_result = "514579\n";
 
             let mut cmd = Command::cargo_bin("day-1")?;
-            cmd.arg(actual_expense_report_file.path()).arg("2");
+            cmd.arg(actual_expense_report_file.path())
+                .arg("--count")
+                .arg("2");
 
             cmd.assert()
                 .success()
@@ -58,15 +103,285 @@ mod given_expense_report {
                     1456
                 " }
             )?;
-            let expected_result = "514579\n";
+            let expected_result = "241861950\n";
+
+            let mut cmd = Command::cargo_bin("day-1")?;
+            cmd.arg(actual_expense_report_file.path())
+                .arg("--count")
+                .arg("3");
+
+            cmd.assert()
+                .success()
+                .stdout(predicate::eq(expected_result));
+            Ok(())
+        }
+    }
+
+    mod when_searching_for_two_expenses_that_sum_to_a_custom_target {
+
+        use assert_cmd::prelude::*;
+        use indoc::indoc;
+        use predicates::prelude::*;
+        use std::io::prelude::*;
+        use std::process::Command;
+        use tempfile::NamedTempFile;
+
+        #[test]
+        fn then_prints_product_of_those_expenses() -> Result<(), Box<dyn std::error::Error>> {
+            let mut actual_expense_report_file = NamedTempFile::new()?;
+            writeln!(
+                actual_expense_report_file,
+                indoc! { "
+                    1721
+                    979
+                    366
+                    299
+                    675
+                    1456
+                " }
+            )?;
+            let expected_result = "1684859\n";
+
+            let mut cmd = Command::cargo_bin("day-1")?;
+            cmd.arg(actual_expense_report_file.path())
+                .arg("--count")
+                .arg("2")
+                .arg("--target")
+                .arg("2700");
+
+            cmd.assert()
+                .success()
+                .stdout(predicate::eq(expected_result));
+            Ok(())
+        }
+
+        #[test]
+        fn then_reports_the_target_when_no_expenses_add_up_to_it(
+        ) -> Result<(), Box<dyn std::error::Error>> {
+            let mut actual_expense_report_file = NamedTempFile::new()?;
+            writeln!(
+                actual_expense_report_file,
+                indoc! { "
+                    1721
+                    979
+                    366
+                    299
+                    675
+                    1456
+                " }
+            )?;
+
+            let mut cmd = Command::cargo_bin("day-1")?;
+            cmd.arg(actual_expense_report_file.path())
+                .arg("--count")
+                .arg("2")
+                .arg("--target")
+                .arg("1");
+
+            cmd.assert()
+                .failure()
+                .stderr(predicate::str::contains("that add up to 1."));
+            Ok(())
+        }
+
+        #[test]
+        fn then_reports_no_match_for_a_target_with_cents() -> Result<(), Box<dyn std::error::Error>>
+        {
+            let mut actual_expense_report_file = NamedTempFile::new()?;
+            writeln!(
+                actual_expense_report_file,
+                indoc! { "
+                    1000
+                    7
+                " }
+            )?;
+
+            let mut cmd = Command::cargo_bin("day-1")?;
+            cmd.arg(actual_expense_report_file.path())
+                .arg("--target")
+                .arg("1007.50");
+
+            cmd.assert()
+                .failure()
+                .stderr(predicate::str::contains("that add up to 1007.50."));
+            Ok(())
+        }
+    }
+
+    mod when_searching_for_all_pairs_of_expenses_that_sum_to_a_custom_target {
+
+        use assert_cmd::prelude::*;
+        use indoc::indoc;
+        use predicates::prelude::*;
+        use std::io::prelude::*;
+        use std::process::Command;
+        use tempfile::NamedTempFile;
+
+        #[test]
+        fn then_prints_every_pair_with_line_numbers_and_product(
+        ) -> Result<(), Box<dyn std::error::Error>> {
+            let mut actual_expense_report_file = NamedTempFile::new()?;
+            writeln!(
+                actual_expense_report_file,
+                indoc! { "
+                    1000
+                    1020
+                    1000
+
+                    1020
+                " }
+            )?;
+            let expected_result = indoc! { "
+                Lines 1, 2: 1000 + 1020 = 2020, product 1020000
+                Lines 1, 5: 1000 + 1020 = 2020, product 1020000
+                Lines 2, 3: 1020 + 1000 = 2020, product 1020000
+                Lines 3, 5: 1000 + 1020 = 2020, product 1020000
+            " };
+
+            let mut cmd = Command::cargo_bin("day-1")?;
+            cmd.arg(actual_expense_report_file.path())
+                .arg("--count")
+                .arg("2")
+                .arg("--all");
+
+            cmd.assert()
+                .success()
+                .stdout(predicate::eq(expected_result));
+            Ok(())
+        }
+    }
+
+    mod when_searching_for_two_expenses_closest_to_a_custom_target {
+
+        use assert_cmd::prelude::*;
+        use indoc::indoc;
+        use predicates::prelude::*;
+        use std::io::prelude::*;
+        use std::process::Command;
+        use tempfile::NamedTempFile;
+
+        #[test]
+        fn then_prints_nearest_pair_with_signed_difference(
+        ) -> Result<(), Box<dyn std::error::Error>> {
+            let mut actual_expense_report_file = NamedTempFile::new()?;
+            writeln!(
+                actual_expense_report_file,
+                indoc! { "
+                    1721
+                    979
+                    366
+                    299
+                    675
+                    1456
+                " }
+            )?;
+            let expected_result = "Lines 3, 5: 366 + 675 = 1041, difference -9, product 247050\n";
+
+            let mut cmd = Command::cargo_bin("day-1")?;
+            cmd.arg(actual_expense_report_file.path())
+                .arg("--count")
+                .arg("2")
+                .arg("--target")
+                .arg("1050")
+                .arg("--closest");
+
+            cmd.assert()
+                .success()
+                .stdout(predicate::eq(expected_result));
+            Ok(())
+        }
+    }
+
+    mod when_searching_for_two_monetary_expenses_that_sum_to_a_decimal_target {
+
+        use assert_cmd::prelude::*;
+        use indoc::indoc;
+        use predicates::prelude::*;
+        use std::io::prelude::*;
+        use std::process::Command;
+        use tempfile::NamedTempFile;
+
+        #[test]
+        fn then_prints_exact_product_of_those_expenses() -> Result<(), Box<dyn std::error::Error>> {
+            let mut actual_expense_report_file = NamedTempFile::new()?;
+            writeln!(
+                actual_expense_report_file,
+                indoc! { "
+                    12.50
+                    -3.07
+                    7
+                " }
+            )?;
+            let expected_result = "-38.3750\n";
 
             let mut cmd = Command::cargo_bin("day-1")?;
-            cmd.arg(actual_expense_report_file.path()).arg("2");
+            cmd.arg(actual_expense_report_file.path())
+                .arg("--count")
+                .arg("2")
+                .arg("--target")
+                .arg("9.43");
 
             cmd.assert()
                 .success()
                 .stdout(predicate::eq(expected_result));
             Ok(())
         }
+
+        #[test]
+        fn then_prints_every_pair_formatted_to_the_cent() -> Result<(), Box<dyn std::error::Error>>
+        {
+            let mut actual_expense_report_file = NamedTempFile::new()?;
+            writeln!(
+                actual_expense_report_file,
+                indoc! { "
+                    12.50
+                    -3.07
+                    7
+                    2.43
+                " }
+            )?;
+            let expected_result = indoc! { "
+                Lines 1, 2: 12.50 + -3.07 = 9.43, product -38.3750
+                Lines 3, 4: 7.00 + 2.43 = 9.43, product 17.0100
+            " };
+
+            let mut cmd = Command::cargo_bin("day-1")?;
+            cmd.arg(actual_expense_report_file.path())
+                .arg("--count")
+                .arg("2")
+                .arg("--target")
+                .arg("9.43")
+                .arg("--all");
+
+            cmd.assert()
+                .success()
+                .stdout(predicate::eq(expected_result));
+            Ok(())
+        }
+    }
+
+    mod when_searching_with_both_all_and_closest {
+
+        use assert_cmd::prelude::*;
+        use predicates::prelude::*;
+        use std::io::prelude::*;
+        use std::process::Command;
+        use tempfile::NamedTempFile;
+
+        #[test]
+        fn then_prints_usage_error() -> Result<(), Box<dyn std::error::Error>> {
+            let mut actual_expense_report_file = NamedTempFile::new()?;
+            writeln!(actual_expense_report_file, "2020")?;
+
+            let mut cmd = Command::cargo_bin("day-1")?;
+            cmd.arg(actual_expense_report_file.path())
+                .arg("--all")
+                .arg("--closest");
+
+            cmd.assert()
+                .failure()
+                .stderr(predicate::str::contains("cannot be used with"));
+            Ok(())
+        }
     }
 }
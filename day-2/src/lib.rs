@@ -0,0 +1,1889 @@
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::fmt;
+use std::io;
+use std::ops::Range;
+use std::str::FromStr;
+use thiserror::Error;
+use unicode_segmentation::UnicodeSegmentation;
+
+pub trait Policy: Sized {
+    fn parse_with(s: &str, segmentation: Segmentation) -> Result<Self, ParseError>;
+
+    fn is_valid(&self, password: &str) -> bool;
+
+    // Explains why `password` is or isn't valid, e.g. "letter 'a' occurs 5 times, allowed 1-3"
+    fn reason(&self, password: &str) -> String;
+
+    // The smallest edit that would make an invalid `password` valid, if this policy knows of one
+    fn fix(&self, _password: &str) -> Option<Fix> {
+        None
+    }
+}
+
+// An edit to a password that makes it satisfy a policy
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Fix {
+    Add {
+        letter: String,
+        count: usize,
+    },
+    Remove {
+        letter: String,
+        count: usize,
+    },
+    Replace {
+        position: usize,
+        letter: String,
+    },
+    // Replace the letter at `position` with any letter except `letter`
+    ReplaceWithOther {
+        position: usize,
+        letter: String,
+    },
+    // Append `padding` letters other than `letter`, and then `letter` itself at `position`
+    Append {
+        padding: usize,
+        position: usize,
+        letter: String,
+    },
+}
+
+impl fmt::Display for Fix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let occurrences = |count: usize| {
+            if count == 1 {
+                "occurrence"
+            } else {
+                "occurrences"
+            }
+        };
+        match self {
+            Fix::Add { letter, count } => {
+                write!(f, "add {} {} of '{}'", count, occurrences(*count), letter)
+            }
+            Fix::Remove { letter, count } => {
+                write!(
+                    f,
+                    "remove {} {} of '{}'",
+                    count,
+                    occurrences(*count),
+                    letter
+                )
+            }
+            Fix::Replace { position, letter } => {
+                write!(f, "replace position {} with '{}'", position, letter)
+            }
+            Fix::ReplaceWithOther { position, letter } => write!(
+                f,
+                "replace position {} with a letter other than '{}'",
+                position, letter
+            ),
+            Fix::Append {
+                padding: 0,
+                position,
+                letter,
+            } => write!(f, "append '{}' as position {}", letter, position),
+            Fix::Append {
+                padding,
+                position,
+                letter,
+            } => write!(
+                f,
+                "append {} {} other than '{}' and then '{}' as position {}",
+                padding,
+                if *padding == 1 { "letter" } else { "letters" },
+                letter,
+                letter,
+                position
+            ),
+        }
+    }
+}
+
+// How passwords and policy letters are split into the letters that policies count and index
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Segmentation {
+    // Unicode code points, i.e. Rust `char`s
+    Chars,
+    // Extended grapheme clusters, i.e. what a user would see as a single character
+    Graphemes,
+}
+
+impl Segmentation {
+    fn letters(self, s: &str) -> Vec<&str> {
+        match self {
+            Segmentation::Chars => s
+                .char_indices()
+                .map(|(index, c)| &s[index..index + c.len_utf8()])
+                .collect(),
+            Segmentation::Graphemes => s.graphemes(true).collect(),
+        }
+    }
+}
+
+pub fn count_valid_passwords<P, I>(lines: I, segmentation: Segmentation) -> Result<usize, Day2Error>
+where
+    P: Policy,
+    I: IntoIterator<Item = io::Result<String>>,
+{
+    let passwords_and_policies = parse_passwords_and_policies::<P, _>(lines, segmentation)?;
+    Ok(passwords_and_policies
+        .iter()
+        .filter(|&p| p.is_valid())
+        .count())
+}
+
+pub fn parse_passwords_and_policies<P, I>(
+    lines: I,
+    segmentation: Segmentation,
+) -> Result<Vec<PasswordAndPolicy<P>>, Day2Error>
+where
+    P: Policy,
+    I: IntoIterator<Item = io::Result<String>>,
+{
+    Ok(parse_lines::<P, _>(lines, segmentation)?
+        .into_iter()
+        .map(|parsed_line| parsed_line.password_and_policy)
+        .collect())
+}
+
+pub fn validation_report<P, I>(
+    lines: I,
+    segmentation: Segmentation,
+) -> Result<Vec<ReportLine>, Day2Error>
+where
+    P: Policy,
+    I: IntoIterator<Item = io::Result<String>>,
+{
+    Ok(parse_lines::<P, _>(lines, segmentation)?
+        .into_iter()
+        .map(|parsed_line| ReportLine {
+            line_number: parsed_line.index + 1,
+            is_valid: parsed_line.password_and_policy.is_valid(),
+            reason: parsed_line.password_and_policy.reason(),
+            fix: parsed_line.password_and_policy.fix(),
+            line: parsed_line.line,
+        })
+        .collect())
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ReportLine {
+    pub line_number: usize,
+    pub line: String,
+    pub is_valid: bool,
+    pub reason: String,
+    pub fix: Option<Fix>,
+}
+
+struct ParsedLine<P: Policy> {
+    index: usize,
+    line: String,
+    password_and_policy: PasswordAndPolicy<P>,
+}
+
+fn parse_lines<P, I>(lines: I, segmentation: Segmentation) -> Result<Vec<ParsedLine<P>>, Day2Error>
+where
+    P: Policy,
+    I: IntoIterator<Item = io::Result<String>>,
+{
+    let mut parsed_lines = Vec::new();
+    for (index, line) in lines.into_iter().enumerate() {
+        let line = line.map_err(|_| Day2Error::UnreadableLine { index })?;
+
+        let line = line.trim();
+        if !line.is_empty() {
+            let password_and_policy = PasswordAndPolicy::<P>::parse_with(line, segmentation)
+                .map_err(|source| Day2Error::InvalidLine {
+                    index,
+                    line: line.to_string(),
+                    source,
+                })?;
+            parsed_lines.push(ParsedLine {
+                index,
+                line: line.to_string(),
+                password_and_policy,
+            });
+        }
+    }
+    Ok(parsed_lines)
+}
+
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct PasswordAndPolicy<P: Policy> {
+    password: String,
+    policy: P,
+}
+
+pub type PasswordAndCountRangePolicy = PasswordAndPolicy<CountRangePolicy>;
+
+pub type PasswordAndPositionsPolicy = PasswordAndPolicy<PositionsPolicy>;
+
+impl<P: Policy> PasswordAndPolicy<P> {
+    pub fn password(&self) -> &str {
+        &self.password
+    }
+
+    pub fn policy(&self) -> &P {
+        &self.policy
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.policy.is_valid(&self.password)
+    }
+
+    pub fn reason(&self) -> String {
+        self.policy.reason(&self.password)
+    }
+
+    pub fn fix(&self) -> Option<Fix> {
+        self.policy.fix(&self.password)
+    }
+
+    pub fn parse_with(s: &str, segmentation: Segmentation) -> Result<Self, ParseError> {
+        // The policy's letter may itself be ':', so the policy only ends early at a lone ':' if
+        // there's no ": " separator at all
+        let policy_end = s.find(": ").or_else(|| s.find(':')).unwrap_or(s.len());
+        let policy = P::parse_with(&s[..policy_end], segmentation)?;
+
+        let separator = ": ";
+        let rest = &s[policy_end..];
+        if !rest.starts_with(separator) {
+            return Err(ParseError::MissingSeparator {
+                column: column_of(s, policy_end),
+                expected: separator,
+                found: rest.chars().take(separator.len()).collect(),
+            });
+        }
+
+        let password_start = policy_end + separator.len();
+        let password = s[password_start..].trim();
+        if password.is_empty() {
+            return Err(ParseError::MissingPassword {
+                column: column_of(s, password_start),
+            });
+        }
+
+        Ok(PasswordAndPolicy {
+            password: password.to_string(),
+            policy,
+        })
+    }
+}
+
+impl<P: Policy> FromStr for PasswordAndPolicy<P> {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        PasswordAndPolicy::parse_with(s, Segmentation::Chars)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct CountRangePolicy {
+    expected_letter: String,
+    expected_letter_count_range: Range<usize>,
+    segmentation: Segmentation,
+}
+
+impl CountRangePolicy {
+    fn expected_letter_count(&self, password: &str) -> usize {
+        self.segmentation
+            .letters(password)
+            .into_iter()
+            .filter(|&letter| letter == self.expected_letter)
+            .count()
+    }
+}
+
+impl Policy for CountRangePolicy {
+    fn parse_with(s: &str, segmentation: Segmentation) -> Result<Self, ParseError> {
+        CountRangePolicy::from_fields(PolicyFields::parse(s, segmentation)?)
+    }
+
+    fn is_valid(&self, password: &str) -> bool {
+        let expected_letter_count = self.expected_letter_count(password);
+        self.expected_letter_count_range
+            .contains(&expected_letter_count)
+    }
+
+    fn reason(&self, password: &str) -> String {
+        let expected_letter_count = self.expected_letter_count(password);
+        format!(
+            "letter '{}' occurs {} {}, allowed {}-{}",
+            self.expected_letter,
+            expected_letter_count,
+            if expected_letter_count == 1 {
+                "time"
+            } else {
+                "times"
+            },
+            self.expected_letter_count_range.start,
+            self.expected_letter_count_range.end - 1
+        )
+    }
+
+    fn fix(&self, password: &str) -> Option<Fix> {
+        let expected_letter_count = self.expected_letter_count(password);
+        let range = &self.expected_letter_count_range;
+        if expected_letter_count < range.start {
+            Some(Fix::Add {
+                letter: self.expected_letter.clone(),
+                count: range.start - expected_letter_count,
+            })
+        } else if expected_letter_count >= range.end {
+            Some(Fix::Remove {
+                letter: self.expected_letter.clone(),
+                count: expected_letter_count - (range.end - 1),
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl FromStr for CountRangePolicy {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        CountRangePolicy::parse_with(s, Segmentation::Chars)
+    }
+}
+
+impl CountRangePolicy {
+    fn from_fields(fields: PolicyFields) -> Result<Self, ParseError> {
+        if fields.first_number > fields.second_number {
+            return Err(ParseError::ReversedRange {
+                column: fields.first_number_column,
+                start: fields.first_number,
+                end: fields.second_number,
+            });
+        }
+        let expected_letter_count_range = Range {
+            start: fields.first_number,
+            end: fields.second_number + 1,
+        };
+
+        Ok(CountRangePolicy {
+            expected_letter: fields.letter,
+            expected_letter_count_range,
+            segmentation: fields.segmentation,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct PositionsPolicy {
+    expected_letter: String,
+    first_expected_position: usize,
+    second_expected_position: usize,
+    segmentation: Segmentation,
+}
+
+impl PositionsPolicy {
+    fn matches(&self, password: &str) -> (bool, bool) {
+        let letters = self.segmentation.letters(password);
+        let matches_at = |position: usize| {
+            letters
+                .get(position - 1)
+                .map(|&l| l == self.expected_letter)
+                .unwrap_or(false)
+        };
+        (
+            matches_at(self.first_expected_position),
+            matches_at(self.second_expected_position),
+        )
+    }
+}
+
+impl Policy for PositionsPolicy {
+    fn parse_with(s: &str, segmentation: Segmentation) -> Result<Self, ParseError> {
+        PositionsPolicy::from_fields(PolicyFields::parse(s, segmentation)?)
+    }
+
+    fn is_valid(&self, password: &str) -> bool {
+        let (first_matches, second_matches) = self.matches(password);
+        first_matches ^ second_matches
+    }
+
+    fn reason(&self, password: &str) -> String {
+        let first = self.first_expected_position;
+        let second = self.second_expected_position;
+        match self.matches(password) {
+            (true, true) => format!("positions {} and {} both match", first, second),
+            (true, false) => format!("only position {} matches", first),
+            (false, true) => format!("only position {} matches", second),
+            (false, false) => format!("neither position {} nor {} matches", first, second),
+        }
+    }
+
+    // A password can't have exactly one match at the same position twice, so such a policy has
+    // no fix.
+    fn fix(&self, password: &str) -> Option<Fix> {
+        let first = self.first_expected_position;
+        let second = self.second_expected_position;
+        if first == second {
+            return None;
+        }
+        let letter = self.expected_letter.clone();
+        match self.matches(password) {
+            (true, true) => Some(Fix::ReplaceWithOther {
+                position: second,
+                letter,
+            }),
+            (false, false) => {
+                let length = self.segmentation.letters(password).len();
+                let position = first.min(second);
+                if position <= length {
+                    Some(Fix::Replace { position, letter })
+                } else {
+                    Some(Fix::Append {
+                        padding: position - length - 1,
+                        position,
+                        letter,
+                    })
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for PositionsPolicy {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        PositionsPolicy::parse_with(s, Segmentation::Chars)
+    }
+}
+
+impl PositionsPolicy {
+    fn from_fields(fields: PolicyFields) -> Result<Self, ParseError> {
+        if fields.first_number == 0 {
+            return Err(ParseError::ZeroPosition {
+                column: fields.first_number_column,
+            });
+        }
+        if fields.second_number == 0 {
+            return Err(ParseError::ZeroPosition {
+                column: fields.second_number_column,
+            });
+        }
+
+        Ok(PositionsPolicy {
+            expected_letter: fields.letter,
+            first_expected_position: fields.first_number,
+            second_expected_position: fields.second_number,
+            segmentation: fields.segmentation,
+        })
+    }
+}
+
+// Both interpretations of the same "<number>-<number> <letter>" policy, so that a password can be
+// checked against each of them after parsing its line just once.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct BothPolicies {
+    count_range: CountRangePolicy,
+    positions: PositionsPolicy,
+}
+
+impl BothPolicies {
+    pub fn count_range(&self) -> &CountRangePolicy {
+        &self.count_range
+    }
+
+    pub fn positions(&self) -> &PositionsPolicy {
+        &self.positions
+    }
+
+    pub fn agreement(&self, password: &str) -> PolicyAgreement {
+        PolicyAgreement::of(
+            self.count_range.is_valid(password),
+            self.positions.is_valid(password),
+        )
+    }
+}
+
+impl Policy for BothPolicies {
+    fn parse_with(s: &str, segmentation: Segmentation) -> Result<Self, ParseError> {
+        let fields = PolicyFields::parse(s, segmentation)?;
+
+        Ok(BothPolicies {
+            count_range: CountRangePolicy::from_fields(fields.clone())?,
+            positions: PositionsPolicy::from_fields(fields)?,
+        })
+    }
+
+    fn is_valid(&self, password: &str) -> bool {
+        self.agreement(password) == PolicyAgreement::ValidUnderBoth
+    }
+
+    fn reason(&self, password: &str) -> String {
+        format!(
+            "count range: {}; positions: {}",
+            self.count_range.reason(password),
+            self.positions.reason(password)
+        )
+    }
+}
+
+impl FromStr for BothPolicies {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        BothPolicies::parse_with(s, Segmentation::Chars)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum PolicyAgreement {
+    ValidUnderBoth,
+    ValidOnlyUnderCountRange,
+    ValidOnlyUnderPositions,
+    ValidUnderNeither,
+}
+
+impl PolicyAgreement {
+    fn of(is_valid_under_count_range: bool, is_valid_under_positions: bool) -> PolicyAgreement {
+        match (is_valid_under_count_range, is_valid_under_positions) {
+            (true, true) => PolicyAgreement::ValidUnderBoth,
+            (true, false) => PolicyAgreement::ValidOnlyUnderCountRange,
+            (false, true) => PolicyAgreement::ValidOnlyUnderPositions,
+            (false, false) => PolicyAgreement::ValidUnderNeither,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ComparedLine {
+    pub line_number: usize,
+    pub line: String,
+    pub agreement: PolicyAgreement,
+}
+
+pub fn compare_policies<I>(
+    lines: I,
+    segmentation: Segmentation,
+) -> Result<Vec<ComparedLine>, Day2Error>
+where
+    I: IntoIterator<Item = io::Result<String>>,
+{
+    Ok(
+        parse_lines::<CompoundPolicy<BothPolicies>, _>(lines, segmentation)?
+            .into_iter()
+            .map(|parsed_line| ComparedLine {
+                line_number: parsed_line.index + 1,
+                agreement: parsed_line
+                    .password_and_policy
+                    .policy
+                    .agreement(&parsed_line.password_and_policy.password),
+                line: parsed_line.line,
+            })
+            .collect(),
+    )
+}
+
+// A policy of several clauses joined by "and", "or" and "not", grouped with parentheses, e.g.
+// "1-3 a and not (position 2 or 4 is b)". Every "<number>-<number> <letter>" clause is read as the
+// base policy `P`, so a line with just one such clause means exactly what it means under `P`.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub enum CompoundPolicy<P: Policy> {
+    Clause(P),
+    // Holds when the letter is at any of the 1-based positions
+    Position {
+        positions: Vec<usize>,
+        letter: String,
+        segmentation: Segmentation,
+    },
+    Not(Box<CompoundPolicy<P>>),
+    And(Vec<CompoundPolicy<P>>),
+    Or(Vec<CompoundPolicy<P>>),
+}
+
+impl<P: Policy> Policy for CompoundPolicy<P> {
+    fn parse_with(s: &str, segmentation: Segmentation) -> Result<Self, ParseError> {
+        if s.split_whitespace().count() <= 2 {
+            return P::parse_with(s, segmentation).map(CompoundPolicy::Clause);
+        }
+
+        let mut parser = CompoundPolicyParser {
+            s,
+            words: words_of(s),
+            next: 0,
+            segmentation,
+        };
+        let policy = parser.parse_or::<P>()?;
+        if let Some(word) = parser.peek() {
+            return Err(ParseError::UnexpectedWord {
+                column: word.column,
+                expected: "\"and\" or \"or\"",
+                found: word.text.to_string(),
+            });
+        }
+        Ok(policy)
+    }
+
+    fn is_valid(&self, password: &str) -> bool {
+        match self {
+            CompoundPolicy::Clause(policy) => policy.is_valid(password),
+            CompoundPolicy::Position { .. } => !self.matching_positions(password).is_empty(),
+            CompoundPolicy::Not(policy) => !policy.is_valid(password),
+            CompoundPolicy::And(policies) => policies.iter().all(|p| p.is_valid(password)),
+            CompoundPolicy::Or(policies) => policies.iter().any(|p| p.is_valid(password)),
+        }
+    }
+
+    fn reason(&self, password: &str) -> String {
+        match self {
+            CompoundPolicy::Clause(policy) => policy.reason(password),
+            CompoundPolicy::Position {
+                positions, letter, ..
+            } => {
+                let matching_positions = self.matching_positions(password);
+                if matching_positions.is_empty() {
+                    format!(
+                        "letter '{}' is not at {}",
+                        letter,
+                        describe_positions(positions, "or")
+                    )
+                } else {
+                    format!(
+                        "letter '{}' is at {}",
+                        letter,
+                        describe_positions(&matching_positions, "and")
+                    )
+                }
+            }
+            CompoundPolicy::Not(policy) => format!("not ({})", policy.reason(password)),
+            CompoundPolicy::And(policies) | CompoundPolicy::Or(policies) => policies
+                .iter()
+                .map(|policy| match policy {
+                    CompoundPolicy::And(_) | CompoundPolicy::Or(_) => {
+                        format!("({})", policy.reason(password))
+                    }
+                    _ => policy.reason(password),
+                })
+                .collect::<Vec<_>>()
+                .join("; "),
+        }
+    }
+
+    fn fix(&self, password: &str) -> Option<Fix> {
+        match self {
+            CompoundPolicy::Clause(policy) => policy.fix(password),
+            _ => None,
+        }
+    }
+}
+
+impl<P: Policy> CompoundPolicy<P> {
+    fn matching_positions(&self, password: &str) -> Vec<usize> {
+        match self {
+            CompoundPolicy::Position {
+                positions,
+                letter,
+                segmentation,
+            } => {
+                let letters = segmentation.letters(password);
+                positions
+                    .iter()
+                    .copied()
+                    .filter(|&position| letters.get(position - 1) == Some(&letter.as_str()))
+                    .collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl CompoundPolicy<BothPolicies> {
+    // Which policies `password` is valid under when every "<number>-<number> <letter>" clause is
+    // read as the count range policy, and when every one is read as the positions policy
+    pub fn agreement(&self, password: &str) -> PolicyAgreement {
+        PolicyAgreement::of(
+            self.is_valid_under(password, &|policies, password| {
+                policies.count_range.is_valid(password)
+            }),
+            self.is_valid_under(password, &|policies, password| {
+                policies.positions.is_valid(password)
+            }),
+        )
+    }
+
+    fn is_valid_under(
+        &self,
+        password: &str,
+        is_clause_valid: &dyn Fn(&BothPolicies, &str) -> bool,
+    ) -> bool {
+        match self {
+            CompoundPolicy::Clause(policies) => is_clause_valid(policies, password),
+            CompoundPolicy::Position { .. } => !self.matching_positions(password).is_empty(),
+            CompoundPolicy::Not(policy) => !policy.is_valid_under(password, is_clause_valid),
+            CompoundPolicy::And(policies) => policies
+                .iter()
+                .all(|p| p.is_valid_under(password, is_clause_valid)),
+            CompoundPolicy::Or(policies) => policies
+                .iter()
+                .any(|p| p.is_valid_under(password, is_clause_valid)),
+        }
+    }
+}
+
+impl<P: Policy> FromStr for CompoundPolicy<P> {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        CompoundPolicy::parse_with(s, Segmentation::Chars)
+    }
+}
+
+// Describes positions like "position 1", "positions 1 or 4" or "positions 1, 2 and 4".
+fn describe_positions(positions: &[usize], conjunction: &str) -> String {
+    match positions {
+        [position] => format!("position {}", position),
+        [init @ .., last] => format!(
+            "positions {} {} {}",
+            init.iter()
+                .map(|position| position.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+            conjunction,
+            last
+        ),
+        [] => "no positions".to_string(),
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Word<'a> {
+    text: &'a str,
+    column: usize,
+}
+
+// Splits a compound policy into words at whitespace, with every parenthesis a word of its own.
+fn words_of(s: &str) -> Vec<Word<'_>> {
+    let mut words = Vec::new();
+    let mut word_start = None;
+    for (index, c) in s.char_indices() {
+        if c.is_whitespace() || c == '(' || c == ')' {
+            if let Some(start) = word_start.take() {
+                words.push(Word {
+                    text: &s[start..index],
+                    column: column_of(s, start),
+                });
+            }
+            if !c.is_whitespace() {
+                words.push(Word {
+                    text: &s[index..index + 1],
+                    column: column_of(s, index),
+                });
+            }
+        } else if word_start.is_none() {
+            word_start = Some(index);
+        }
+    }
+    if let Some(start) = word_start {
+        words.push(Word {
+            text: &s[start..],
+            column: column_of(s, start),
+        });
+    }
+    words
+}
+
+// A recursive descent parser for compound policies, where "not" binds tighter than "and", which
+// binds tighter than "or".
+struct CompoundPolicyParser<'a> {
+    s: &'a str,
+    words: Vec<Word<'a>>,
+    next: usize,
+    segmentation: Segmentation,
+}
+
+impl<'a> CompoundPolicyParser<'a> {
+    fn peek(&self) -> Option<&Word<'a>> {
+        self.words.get(self.next)
+    }
+
+    fn peek_is(&self, text: &str) -> bool {
+        self.peek().map(|word| word.text == text).unwrap_or(false)
+    }
+
+    fn advance(&mut self, expected: &'static str) -> Result<Word<'a>, ParseError> {
+        match self.words.get(self.next).copied() {
+            Some(word) => {
+                self.next += 1;
+                Ok(word)
+            }
+            None => Err(ParseError::UnexpectedWord {
+                column: column_of(self.s, self.s.len()),
+                expected,
+                found: String::new(),
+            }),
+        }
+    }
+
+    fn expect(&mut self, expected: &'static str) -> Result<(), ParseError> {
+        let word = self.advance(expected)?;
+        if word.text != expected {
+            return Err(ParseError::UnexpectedWord {
+                column: word.column,
+                expected,
+                found: word.text.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    fn parse_or<P: Policy>(&mut self) -> Result<CompoundPolicy<P>, ParseError> {
+        let mut policies = vec![self.parse_and()?];
+        while self.peek_is("or") {
+            self.next += 1;
+            policies.push(self.parse_and()?);
+        }
+        Ok(if policies.len() == 1 {
+            policies.remove(0)
+        } else {
+            CompoundPolicy::Or(policies)
+        })
+    }
+
+    fn parse_and<P: Policy>(&mut self) -> Result<CompoundPolicy<P>, ParseError> {
+        let mut policies = vec![self.parse_not()?];
+        while self.peek_is("and") {
+            self.next += 1;
+            policies.push(self.parse_not()?);
+        }
+        Ok(if policies.len() == 1 {
+            policies.remove(0)
+        } else {
+            CompoundPolicy::And(policies)
+        })
+    }
+
+    fn parse_not<P: Policy>(&mut self) -> Result<CompoundPolicy<P>, ParseError> {
+        if self.peek_is("not") {
+            self.next += 1;
+            return Ok(CompoundPolicy::Not(Box::new(self.parse_not()?)));
+        }
+        if self.peek_is("(") {
+            self.next += 1;
+            let policy = self.parse_or()?;
+            self.expect(")")?;
+            return Ok(policy);
+        }
+        if self.peek_is("position") {
+            self.next += 1;
+            return self.parse_position_clause();
+        }
+        self.parse_clause()
+    }
+
+    // Parses the rest of a "position <number> [or <number>...] is <letter>" clause.
+    fn parse_position_clause<P: Policy>(&mut self) -> Result<CompoundPolicy<P>, ParseError> {
+        let mut positions = Vec::new();
+        loop {
+            let word = self.advance("a position like \"4\"")?;
+            let position = word
+                .text
+                .parse::<usize>()
+                .map_err(|_| ParseError::UnexpectedWord {
+                    column: word.column,
+                    expected: "a position like \"4\"",
+                    found: word.text.to_string(),
+                })?;
+            if position == 0 {
+                return Err(ParseError::ZeroPosition {
+                    column: word.column,
+                });
+            }
+            positions.push(position);
+
+            let another_position_follows = self.peek_is("or")
+                && self
+                    .words
+                    .get(self.next + 1)
+                    .map(|word| word.text.parse::<usize>().is_ok())
+                    .unwrap_or(false);
+            if !another_position_follows {
+                break;
+            }
+            self.next += 1;
+        }
+        self.expect("is")?;
+
+        let word = self.advance("a single letter")?;
+        if self.segmentation.letters(word.text).len() != 1 {
+            return Err(ParseError::InvalidLetter {
+                column: word.column,
+                found: word.text.to_string(),
+            });
+        }
+
+        Ok(CompoundPolicy::Position {
+            positions,
+            letter: word.text.to_string(),
+            segmentation: self.segmentation,
+        })
+    }
+
+    // Parses a "<number>-<number> <letter>" clause with the base policy, reporting any error at
+    // its column in the whole compound policy.
+    fn parse_clause<P: Policy>(&mut self) -> Result<CompoundPolicy<P>, ParseError> {
+        let range = self.advance("a range like \"1-3\"")?;
+        let letter = self.advance("a single letter")?;
+        let range_length = range.text.chars().count();
+
+        P::parse_with(
+            &format!("{} {}", range.text, letter.text),
+            self.segmentation,
+        )
+        .map(CompoundPolicy::Clause)
+        .map_err(|err| {
+            err.map_column(|column| {
+                if column <= range_length + 1 {
+                    range.column + column - 1
+                } else {
+                    letter.column + column - range_length - 2
+                }
+            })
+        })
+    }
+}
+
+// How `generate_lines` should make up a password database
+#[derive(Debug, PartialEq, Clone)]
+pub struct GeneratorOptions {
+    pub line_count: usize,
+    pub seed: u64,
+    // The chance that a line's password is valid under `CountRangePolicy`
+    pub count_range_valid_ratio: f64,
+    // The chance that a line's password is valid under `PositionsPolicy`
+    pub positions_valid_ratio: f64,
+    // The chance that a line is deliberately broken so that neither policy can parse it
+    pub malformed_ratio: f64,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct GeneratedLine {
+    pub line: String,
+    pub is_malformed: bool,
+    pub is_valid_under_count_range: bool,
+    pub is_valid_under_positions: bool,
+}
+
+const GENERATED_LETTERS: &str = "abcdefghijklmnopqrstuvwxyz";
+
+// Makes up password-and-policy lines, the same ones every time for the same options.
+//
+// Each policy is "<low>-<high> <letter>" with `low < high`, so that a password can be made valid or
+// invalid under each policy independently of the other.
+pub fn generate_lines(options: &GeneratorOptions) -> Vec<GeneratedLine> {
+    let mut rng = ChaCha8Rng::seed_from_u64(options.seed);
+    (0..options.line_count)
+        .map(|_| {
+            let is_valid_under_count_range = rng.gen_bool(options.count_range_valid_ratio);
+            let is_valid_under_positions = rng.gen_bool(options.positions_valid_ratio);
+            let line = generate_line(
+                &mut rng,
+                is_valid_under_count_range,
+                is_valid_under_positions,
+            );
+            if rng.gen_bool(options.malformed_ratio) {
+                GeneratedLine {
+                    line: malform_line(&mut rng, &line),
+                    is_malformed: true,
+                    is_valid_under_count_range: false,
+                    is_valid_under_positions: false,
+                }
+            } else {
+                GeneratedLine {
+                    line,
+                    is_malformed: false,
+                    is_valid_under_count_range,
+                    is_valid_under_positions,
+                }
+            }
+        })
+        .collect()
+}
+
+fn generate_line<R: Rng>(
+    rng: &mut R,
+    is_valid_under_count_range: bool,
+    is_valid_under_positions: bool,
+) -> String {
+    let letters = GENERATED_LETTERS.chars().collect::<Vec<_>>();
+    let letter = *letters.choose(rng).unwrap();
+    let other_letters = letters
+        .iter()
+        .copied()
+        .filter(|&l| l != letter)
+        .collect::<Vec<_>>();
+    let low = rng.gen_range(1, 6);
+    let high = rng.gen_range(low + 1, low + 10);
+
+    // How many of the two positions hold the letter: exactly one for a valid password, otherwise
+    // neither or both
+    let letters_at_positions = if is_valid_under_positions {
+        1
+    } else if rng.gen_bool(0.5) {
+        0
+    } else {
+        2
+    };
+    let letter_count = if is_valid_under_count_range {
+        rng.gen_range(low.max(letters_at_positions), high + 1)
+    } else if letters_at_positions < low && rng.gen_bool(0.5) {
+        rng.gen_range(letters_at_positions, low)
+    } else {
+        rng.gen_range(high + 1, high + 4)
+    };
+
+    let letters_elsewhere = letter_count - letters_at_positions;
+    let length = high.max(letters_elsewhere + 2) + rng.gen_range(0, 4);
+    let mut password = (0..length)
+        .map(|_| *other_letters.choose(rng).unwrap())
+        .collect::<Vec<_>>();
+    let positions_with_letter = match letters_at_positions {
+        0 => vec![],
+        1 => vec![*[low, high].choose(rng).unwrap()],
+        _ => vec![low, high],
+    };
+    for position in positions_with_letter {
+        password[position - 1] = letter;
+    }
+    let other_indices = (0..length)
+        .filter(|&index| index != low - 1 && index != high - 1)
+        .collect::<Vec<_>>();
+    for &index in other_indices.choose_multiple(rng, letters_elsewhere) {
+        password[index] = letter;
+    }
+
+    format!(
+        "{}-{} {}: {}",
+        low,
+        high,
+        letter,
+        password.into_iter().collect::<String>()
+    )
+}
+
+// Breaks a well-formed line in one of the ways that both policies reject.
+fn malform_line<R: Rng>(rng: &mut R, line: &str) -> String {
+    let (policy, password) = line.split_at(line.find(": ").unwrap());
+    match rng.gen_range(0, 4) {
+        0 => line.replacen('-', "", 1),
+        1 => {
+            let letter = policy.chars().last().unwrap();
+            format!("{}{}{}", policy, letter, password)
+        }
+        2 => format!("{} {}", policy, &password[2..]),
+        _ => format!("{}:", policy),
+    }
+}
+
+// The parts of a "<number>-<number> <letter>" policy, which both kinds of policy share
+#[derive(Clone)]
+struct PolicyFields {
+    first_number: usize,
+    first_number_column: usize,
+    second_number: usize,
+    second_number_column: usize,
+    letter: String,
+    segmentation: Segmentation,
+}
+
+impl PolicyFields {
+    fn parse(s: &str, segmentation: Segmentation) -> Result<PolicyFields, ParseError> {
+        let range_end = s.find(' ').unwrap_or(s.len());
+        let range = &s[..range_end];
+        let invalid_range = || ParseError::InvalidRange {
+            column: 1,
+            found: range.to_string(),
+        };
+        let dash = range.find('-').ok_or_else(invalid_range)?;
+        let first_number = range[..dash]
+            .parse::<usize>()
+            .map_err(|_| invalid_range())?;
+        let second_number = range[dash + 1..]
+            .parse::<usize>()
+            .map_err(|_| invalid_range())?;
+
+        if range_end == s.len() {
+            return Err(ParseError::MissingSeparator {
+                column: column_of(s, range_end),
+                expected: " ",
+                found: String::new(),
+            });
+        }
+
+        let letter_start = range_end + 1;
+        let letter = &s[letter_start..];
+        if segmentation.letters(letter).len() != 1 || letter.trim().is_empty() {
+            return Err(ParseError::InvalidLetter {
+                column: column_of(s, letter_start),
+                found: letter.to_string(),
+            });
+        }
+
+        Ok(PolicyFields {
+            first_number,
+            first_number_column: 1,
+            second_number,
+            second_number_column: column_of(s, dash + 1),
+            letter: letter.to_string(),
+            segmentation,
+        })
+    }
+}
+
+// Converts a byte offset into `s` into a 1-based column, counting characters rather than bytes.
+fn column_of(s: &str, byte_offset: usize) -> usize {
+    s[..byte_offset].chars().count() + 1
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum Day2Error {
+    #[error("Line {index} cannot be read.")]
+    UnreadableLine { index: usize },
+    #[error("Line {index} is not a password-and-policy string: \"{line}\". Reason: {source}.")]
+    InvalidLine {
+        index: usize,
+        line: String,
+        source: ParseError,
+    },
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    #[error("expected a range like \"1-3\" at column {column}, but found \"{found}\"")]
+    InvalidRange { column: usize, found: String },
+    #[error(
+        "the range \"{start}-{end}\" at column {column} is reversed; expected \"{end}-{start}\""
+    )]
+    ReversedRange {
+        column: usize,
+        start: usize,
+        end: usize,
+    },
+    #[error("positions start at 1, but found position 0 at column {column}")]
+    ZeroPosition { column: usize },
+    #[error("expected a single letter at column {column}, but found \"{found}\"")]
+    InvalidLetter { column: usize, found: String },
+    #[error("expected separator \"{expected}\" at column {column}, but found \"{found}\"")]
+    MissingSeparator {
+        column: usize,
+        expected: &'static str,
+        found: String,
+    },
+    #[error("expected a password at column {column}, but found nothing")]
+    MissingPassword { column: usize },
+    #[error("expected {expected} at column {column}, but found \"{found}\"")]
+    UnexpectedWord {
+        column: usize,
+        expected: &'static str,
+        found: String,
+    },
+}
+
+impl ParseError {
+    fn map_column<F: Fn(usize) -> usize>(self, f: F) -> Self {
+        match self {
+            ParseError::InvalidRange { column, found } => ParseError::InvalidRange {
+                column: f(column),
+                found,
+            },
+            ParseError::ReversedRange { column, start, end } => ParseError::ReversedRange {
+                column: f(column),
+                start,
+                end,
+            },
+            ParseError::ZeroPosition { column } => ParseError::ZeroPosition { column: f(column) },
+            ParseError::InvalidLetter { column, found } => ParseError::InvalidLetter {
+                column: f(column),
+                found,
+            },
+            ParseError::MissingSeparator {
+                column,
+                expected,
+                found,
+            } => ParseError::MissingSeparator {
+                column: f(column),
+                expected,
+                found,
+            },
+            ParseError::MissingPassword { column } => {
+                ParseError::MissingPassword { column: f(column) }
+            }
+            ParseError::UnexpectedWord {
+                column,
+                expected,
+                found,
+            } => ParseError::UnexpectedWord {
+                column: f(column),
+                expected,
+                found,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn count_range_policy_from_str() {
+        let policy = "1-3 a".parse::<CountRangePolicy>();
+        assert!(policy.is_ok());
+        let policy = policy.unwrap();
+        assert_eq!(policy.expected_letter, "a");
+        assert_eq!(policy.expected_letter_count_range, (1..4));
+    }
+
+    #[test]
+    fn positions_policy_from_str() {
+        let policy = "1-3 a".parse::<PositionsPolicy>();
+        assert!(policy.is_ok());
+        let policy = policy.unwrap();
+        assert_eq!(policy.expected_letter, "a");
+        assert_eq!(policy.first_expected_position, 1);
+        assert_eq!(policy.second_expected_position, 3);
+    }
+
+    #[test]
+    fn password_and_count_range_policy_from_str() {
+        let password_and_policy = "1-3 a: abcde".parse::<PasswordAndCountRangePolicy>();
+        assert!(password_and_policy.is_ok());
+        let password_and_policy = password_and_policy.unwrap();
+        assert_eq!(
+            password_and_policy,
+            PasswordAndCountRangePolicy {
+                password: "abcde".to_string(),
+                policy: CountRangePolicy {
+                    expected_letter: "a".to_string(),
+                    expected_letter_count_range: (1..4),
+                    segmentation: Segmentation::Chars,
+                },
+            }
+        )
+    }
+
+    #[test]
+    fn password_and_positions_policy_from_str() {
+        let password_and_policy = "1-3 a: abcde".parse::<PasswordAndPositionsPolicy>();
+        assert!(password_and_policy.is_ok());
+        let password_and_policy = password_and_policy.unwrap();
+        assert_eq!(
+            password_and_policy,
+            PasswordAndPositionsPolicy {
+                password: "abcde".to_string(),
+                policy: PositionsPolicy {
+                    expected_letter: "a".to_string(),
+                    first_expected_position: 1,
+                    second_expected_position: 3,
+                    segmentation: Segmentation::Chars,
+                },
+            }
+        )
+    }
+
+    #[test]
+    fn password_and_count_range_policy_from_str_with_colon_letter() {
+        let password_and_policy = "1-3 :: a:b".parse::<PasswordAndCountRangePolicy>();
+        assert_eq!(
+            password_and_policy,
+            Ok(PasswordAndCountRangePolicy {
+                password: "a:b".to_string(),
+                policy: CountRangePolicy {
+                    expected_letter: ":".to_string(),
+                    expected_letter_count_range: (1..4),
+                    segmentation: Segmentation::Chars,
+                },
+            })
+        );
+        assert!(password_and_policy.unwrap().is_valid());
+    }
+
+    #[test]
+    fn password_and_count_range_policy_is_valid() {
+        let a = "1-3 a: abcde"
+            .parse::<PasswordAndCountRangePolicy>()
+            .unwrap();
+        assert!(a.is_valid());
+
+        let b = "1-3 b: cdefg"
+            .parse::<PasswordAndCountRangePolicy>()
+            .unwrap();
+        assert!(!b.is_valid());
+
+        let c = "2-9 c: ccccccccc"
+            .parse::<PasswordAndCountRangePolicy>()
+            .unwrap();
+        assert!(c.is_valid());
+    }
+
+    #[test]
+    fn password_and_positions_policy_is_valid() {
+        let a = "1-3 a: abcde"
+            .parse::<PasswordAndPositionsPolicy>()
+            .unwrap();
+        assert!(a.is_valid());
+
+        let b = "1-3 b: bcdef"
+            .parse::<PasswordAndPositionsPolicy>()
+            .unwrap();
+        assert!(b.is_valid());
+
+        let c = "1-3 b: aabcd"
+            .parse::<PasswordAndPositionsPolicy>()
+            .unwrap();
+        assert!(c.is_valid());
+
+        let d = "1-3 b: babcd"
+            .parse::<PasswordAndPositionsPolicy>()
+            .unwrap();
+        assert!(!d.is_valid());
+
+        let e = "2-9 c: ccccccccc"
+            .parse::<PasswordAndPositionsPolicy>()
+            .unwrap();
+        assert!(!e.is_valid());
+    }
+
+    #[test]
+    fn count_range_policy_from_str_with_reversed_range() {
+        let policy = "5-2 a".parse::<CountRangePolicy>();
+        assert_eq!(
+            policy,
+            Err(ParseError::ReversedRange {
+                column: 1,
+                start: 5,
+                end: 2
+            })
+        );
+    }
+
+    #[test]
+    fn positions_policy_from_str_with_zero_position() {
+        let policy = "1-0 a".parse::<PositionsPolicy>();
+        assert_eq!(policy, Err(ParseError::ZeroPosition { column: 3 }));
+    }
+
+    #[test]
+    fn password_and_count_range_policy_from_str_with_invalid_parts() {
+        let cases = vec![
+            (
+                "1/3 a: abcde",
+                ParseError::InvalidRange {
+                    column: 1,
+                    found: "1/3".to_string(),
+                },
+            ),
+            (
+                "1-x a: abcde",
+                ParseError::InvalidRange {
+                    column: 1,
+                    found: "1-x".to_string(),
+                },
+            ),
+            (
+                "1-3: abcde",
+                ParseError::MissingSeparator {
+                    column: 4,
+                    expected: " ",
+                    found: "".to_string(),
+                },
+            ),
+            (
+                "1-3 ab: abcde",
+                ParseError::InvalidLetter {
+                    column: 5,
+                    found: "ab".to_string(),
+                },
+            ),
+            (
+                "1-3 a abcde",
+                ParseError::InvalidLetter {
+                    column: 5,
+                    found: "a abcde".to_string(),
+                },
+            ),
+            (
+                "1-3 a:abcde",
+                ParseError::MissingSeparator {
+                    column: 6,
+                    expected: ": ",
+                    found: ":a".to_string(),
+                },
+            ),
+            ("1-3 a: ", ParseError::MissingPassword { column: 8 }),
+        ];
+
+        for (line, expected_error) in cases {
+            let password_and_policy = line.parse::<PasswordAndCountRangePolicy>();
+            assert_eq!(password_and_policy, Err(expected_error), "{}", line);
+        }
+    }
+
+    #[test]
+    fn parse_passwords_and_policies_with_invalid_line() {
+        let lines = vec!["1-3 a: abcde", "5-2 b: cdefg"]
+            .into_iter()
+            .map(|line| Ok(line.to_string()));
+
+        let error = parse_passwords_and_policies::<CountRangePolicy, _>(lines, Segmentation::Chars)
+            .unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "Line 1 is not a password-and-policy string: \"5-2 b: cdefg\". \
+             Reason: the range \"5-2\" at column 1 is reversed; expected \"2-5\"."
+        );
+    }
+
+    #[test]
+    fn count_range_policy_reason() {
+        let policy = "1-3 a".parse::<CountRangePolicy>().unwrap();
+        assert_eq!(
+            policy.reason("abcde"),
+            "letter 'a' occurs 1 time, allowed 1-3"
+        );
+        assert_eq!(
+            policy.reason("aaaaa"),
+            "letter 'a' occurs 5 times, allowed 1-3"
+        );
+        assert_eq!(
+            policy.reason("bcd"),
+            "letter 'a' occurs 0 times, allowed 1-3"
+        );
+    }
+
+    #[test]
+    fn positions_policy_reason() {
+        let policy = "1-3 a".parse::<PositionsPolicy>().unwrap();
+        assert_eq!(policy.reason("abade"), "positions 1 and 3 both match");
+        assert_eq!(policy.reason("abcde"), "only position 1 matches");
+        assert_eq!(policy.reason("bcade"), "only position 3 matches");
+        assert_eq!(policy.reason("bcdea"), "neither position 1 nor 3 matches");
+        assert_eq!(policy.reason("b"), "neither position 1 nor 3 matches");
+    }
+
+    #[test]
+    fn validation_report_by_count_range_policy() {
+        let lines = vec!["1-3 a: abcde", "", "1-3 b: cdefg"]
+            .into_iter()
+            .map(|line| Ok(line.to_string()));
+
+        let report = validation_report::<CountRangePolicy, _>(lines, Segmentation::Chars);
+
+        assert_eq!(
+            report,
+            Ok(vec![
+                ReportLine {
+                    line_number: 1,
+                    line: "1-3 a: abcde".to_string(),
+                    is_valid: true,
+                    reason: "letter 'a' occurs 1 time, allowed 1-3".to_string(),
+                    fix: None,
+                },
+                ReportLine {
+                    line_number: 3,
+                    line: "1-3 b: cdefg".to_string(),
+                    is_valid: false,
+                    reason: "letter 'b' occurs 0 times, allowed 1-3".to_string(),
+                    fix: Some(Fix::Add {
+                        letter: "b".to_string(),
+                        count: 1
+                    }),
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn compare_policies_reads_every_clause_of_a_compound_policy_one_way_at_a_time() {
+        let lines = vec![
+            "1-3 a and 2-9 b: abbde",
+            "1-3 a or position 9 is x: bacde",
+            "not 1-2 d: dxddd",
+            "not 2-9 c: ccccccccc",
+        ]
+        .into_iter()
+        .map(|line| Ok(line.to_string()));
+
+        let comparison = compare_policies(lines, Segmentation::Chars).unwrap();
+
+        assert_eq!(
+            comparison
+                .iter()
+                .map(|compared_line| (compared_line.line_number, compared_line.agreement))
+                .collect::<Vec<_>>(),
+            vec![
+                (1, PolicyAgreement::ValidUnderBoth),
+                (2, PolicyAgreement::ValidOnlyUnderCountRange),
+                (3, PolicyAgreement::ValidOnlyUnderCountRange),
+                (4, PolicyAgreement::ValidOnlyUnderPositions),
+            ]
+        );
+    }
+
+    #[test]
+    fn compare_policies_puts_each_line_in_one_bucket() {
+        let lines = vec![
+            "1-3 a: abcde",
+            "1-3 b: cdefg",
+            "2-9 c: ccccccccc",
+            "1-2 d: dxddd",
+        ]
+        .into_iter()
+        .map(|line| Ok(line.to_string()));
+
+        let comparison = compare_policies(lines, Segmentation::Chars).unwrap();
+
+        assert_eq!(
+            comparison
+                .iter()
+                .map(|compared_line| (compared_line.line_number, compared_line.agreement))
+                .collect::<Vec<_>>(),
+            vec![
+                (1, PolicyAgreement::ValidUnderBoth),
+                (2, PolicyAgreement::ValidUnderNeither),
+                (3, PolicyAgreement::ValidOnlyUnderCountRange),
+                (4, PolicyAgreement::ValidOnlyUnderPositions),
+            ]
+        );
+    }
+
+    #[test]
+    fn count_valid_passwords_by_either_policy() {
+        let lines = || {
+            vec!["1-3 a: abcde", "1-3 b: cdefg", "", "2-9 c: ccccccccc"]
+                .into_iter()
+                .map(|line| Ok(line.to_string()))
+        };
+
+        assert_eq!(
+            count_valid_passwords::<CountRangePolicy, _>(lines(), Segmentation::Chars),
+            Ok(2)
+        );
+        assert_eq!(
+            count_valid_passwords::<PositionsPolicy, _>(lines(), Segmentation::Chars),
+            Ok(1)
+        );
+    }
+
+    #[test]
+    fn count_range_policy_with_graphemes() {
+        // "e\u{301}" is "é" written as "e" followed by a combining acute accent
+        let password = "e\u{301}xe\u{301}e";
+
+        let by_chars = "1-1 e".parse::<CountRangePolicy>().unwrap();
+        assert!(!by_chars.is_valid(password));
+
+        let by_graphemes = CountRangePolicy::parse_with("1-1 e", Segmentation::Graphemes).unwrap();
+        assert!(by_graphemes.is_valid(password));
+
+        let accented =
+            CountRangePolicy::parse_with("2-2 e\u{301}", Segmentation::Graphemes).unwrap();
+        assert!(accented.is_valid(password));
+        assert_eq!(
+            accented.reason(password),
+            "letter 'e\u{301}' occurs 2 times, allowed 2-2"
+        );
+    }
+
+    #[test]
+    fn positions_policy_with_graphemes() {
+        let password = "\u{1F44D}\u{1F3FD}a\u{1F44D}";
+
+        let by_chars = "3-4 a".parse::<PositionsPolicy>().unwrap();
+        assert!(by_chars.is_valid(password));
+
+        let by_graphemes = PositionsPolicy::parse_with("2-3 a", Segmentation::Graphemes).unwrap();
+        assert!(by_graphemes.is_valid(password));
+
+        let emoji =
+            PositionsPolicy::parse_with("1-3 \u{1F44D}\u{1F3FD}", Segmentation::Graphemes).unwrap();
+        assert_eq!(emoji.reason(password), "only position 1 matches");
+    }
+
+    #[test]
+    fn multi_code_point_letter_is_rejected_without_graphemes() {
+        let policy = "1-3 e\u{301}".parse::<CountRangePolicy>();
+        assert_eq!(
+            policy,
+            Err(ParseError::InvalidLetter {
+                column: 5,
+                found: "e\u{301}".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn compound_policy_with_a_single_clause_matches_its_base_policy() {
+        for line in &["1-3 a: abcde", "1-3 b: cdefg", "2-9 c: ccccccccc"] {
+            let compound = line
+                .parse::<PasswordAndPolicy<CompoundPolicy<CountRangePolicy>>>()
+                .unwrap();
+            let base = line.parse::<PasswordAndCountRangePolicy>().unwrap();
+            assert_eq!(compound.is_valid(), base.is_valid());
+            assert_eq!(compound.reason(), base.reason());
+
+            let compound = line
+                .parse::<PasswordAndPolicy<CompoundPolicy<PositionsPolicy>>>()
+                .unwrap();
+            let base = line.parse::<PasswordAndPositionsPolicy>().unwrap();
+            assert_eq!(compound.is_valid(), base.is_valid());
+            assert_eq!(compound.reason(), base.reason());
+        }
+    }
+
+    #[test]
+    fn compound_policy_from_str() {
+        let policy = "1-3 a and not (position 2 or 4 is b or 2-9 c)"
+            .parse::<CompoundPolicy<CountRangePolicy>>()
+            .unwrap();
+        assert_eq!(
+            policy,
+            CompoundPolicy::And(vec![
+                CompoundPolicy::Clause("1-3 a".parse().unwrap()),
+                CompoundPolicy::Not(Box::new(CompoundPolicy::Or(vec![
+                    CompoundPolicy::Position {
+                        positions: vec![2, 4],
+                        letter: "b".to_string(),
+                        segmentation: Segmentation::Chars
+                    },
+                    CompoundPolicy::Clause("2-9 c".parse().unwrap()),
+                ]))),
+            ])
+        );
+    }
+
+    #[test]
+    fn compound_policy_is_valid() {
+        let policy = "1-3 a and 2-9 b"
+            .parse::<CompoundPolicy<CountRangePolicy>>()
+            .unwrap();
+        assert!(policy.is_valid("abb"));
+        assert!(!policy.is_valid("abc"));
+
+        let policy = "position 1 or 4 is x"
+            .parse::<CompoundPolicy<CountRangePolicy>>()
+            .unwrap();
+        assert!(policy.is_valid("xaaa"));
+        assert!(policy.is_valid("aaax"));
+        assert!(policy.is_valid("xaax"));
+        assert!(!policy.is_valid("axxa"));
+
+        let policy = "not 1-1 a or position 1 is b"
+            .parse::<CompoundPolicy<CountRangePolicy>>()
+            .unwrap();
+        assert!(policy.is_valid("aa"));
+        assert!(policy.is_valid("ba"));
+        assert!(!policy.is_valid("ab"));
+    }
+
+    #[test]
+    fn compound_policy_reason() {
+        let policy = "1-3 a and (position 1 or 4 is x or not 2-9 b)"
+            .parse::<CompoundPolicy<CountRangePolicy>>()
+            .unwrap();
+        assert_eq!(
+            policy.reason("xbca"),
+            "letter 'a' occurs 1 time, allowed 1-3; \
+             (letter 'x' is at position 1; not (letter 'b' occurs 1 time, allowed 2-9))"
+        );
+        assert_eq!(
+            "not 2-9 c"
+                .parse::<CompoundPolicy<CountRangePolicy>>()
+                .unwrap()
+                .reason("ccccccccc"),
+            "not (letter 'c' occurs 9 times, allowed 2-9)"
+        );
+        assert_eq!(
+            "position 1 or 4 is x"
+                .parse::<CompoundPolicy<CountRangePolicy>>()
+                .unwrap()
+                .reason("abcd"),
+            "letter 'x' is not at positions 1 or 4"
+        );
+    }
+
+    #[test]
+    fn compound_policy_from_str_with_invalid_parts() {
+        let table = vec![
+            (
+                "1-3 a and 5-2 b",
+                ParseError::ReversedRange {
+                    column: 11,
+                    start: 5,
+                    end: 2,
+                },
+            ),
+            (
+                "1-3 a and 1-3 bc",
+                ParseError::InvalidLetter {
+                    column: 15,
+                    found: "bc".to_string(),
+                },
+            ),
+            (
+                "1-3 a and (2-9 b",
+                ParseError::UnexpectedWord {
+                    column: 17,
+                    expected: ")",
+                    found: String::new(),
+                },
+            ),
+            ("position 0 is a", ParseError::ZeroPosition { column: 10 }),
+            (
+                "position 1 was a",
+                ParseError::UnexpectedWord {
+                    column: 12,
+                    expected: "is",
+                    found: "was".to_string(),
+                },
+            ),
+            (
+                "1-3 a 2-9 b",
+                ParseError::UnexpectedWord {
+                    column: 7,
+                    expected: "\"and\" or \"or\"",
+                    found: "2-9".to_string(),
+                },
+            ),
+        ];
+        for (policy, expected_error) in table {
+            assert_eq!(
+                policy.parse::<CompoundPolicy<CountRangePolicy>>(),
+                Err(expected_error),
+                "policy: {}",
+                policy
+            );
+        }
+    }
+
+    #[test]
+    fn count_range_policy_fix() {
+        let policy = "2-3 c".parse::<CountRangePolicy>().unwrap();
+        assert_eq!(policy.fix("abcc"), None);
+        assert_eq!(
+            policy.fix("ccccc").map(|fix| fix.to_string()),
+            Some("remove 2 occurrences of 'c'".to_string())
+        );
+        assert_eq!(
+            policy.fix("abc").map(|fix| fix.to_string()),
+            Some("add 1 occurrence of 'c'".to_string())
+        );
+    }
+
+    #[test]
+    fn positions_policy_fix() {
+        let policy = "1-3 a".parse::<PositionsPolicy>().unwrap();
+        let fix_of = |password| policy.fix(password).map(|fix| fix.to_string());
+        assert_eq!(fix_of("abcde"), None);
+        assert_eq!(
+            fix_of("bbcde"),
+            Some("replace position 1 with 'a'".to_string())
+        );
+        assert_eq!(
+            fix_of("abade"),
+            Some("replace position 3 with a letter other than 'a'".to_string())
+        );
+
+        let policy = "4-6 a".parse::<PositionsPolicy>().unwrap();
+        let fix_of = |password| policy.fix(password).map(|fix| fix.to_string());
+        assert_eq!(fix_of("bcd"), Some("append 'a' as position 4".to_string()));
+        assert_eq!(
+            fix_of("b"),
+            Some("append 2 letters other than 'a' and then 'a' as position 4".to_string())
+        );
+
+        let policy = "2-2 a".parse::<PositionsPolicy>().unwrap();
+        assert_eq!(policy.fix("bcd"), None);
+    }
+
+    #[test]
+    fn fixed_passwords_are_valid() {
+        let apply = |password: &str, fix: Fix| -> String {
+            let mut letters = password.chars().map(String::from).collect::<Vec<_>>();
+            match fix {
+                Fix::Add { letter, count } => letters.extend(vec![letter; count]),
+                Fix::Remove { letter, count } => {
+                    for _ in 0..count {
+                        let index = letters.iter().position(|l| *l == letter).unwrap();
+                        letters.remove(index);
+                    }
+                }
+                Fix::Replace { position, letter } => letters[position - 1] = letter,
+                Fix::ReplaceWithOther { position, .. } => letters[position - 1] = "-".to_string(),
+                Fix::Append {
+                    padding, letter, ..
+                } => {
+                    letters.extend(vec!["-".to_string(); padding]);
+                    letters.push(letter);
+                }
+            }
+            letters.concat()
+        };
+        for policy in &["1-3 a", "2-4 a", "3-3 b", "5-7 a"] {
+            for password in &["", "a", "aaaaaa", "abab", "bbbb", "bab"] {
+                let count_range = policy.parse::<CountRangePolicy>().unwrap();
+                if let Some(fix) = count_range.fix(password) {
+                    assert!(count_range.is_valid(&apply(password, fix)));
+                }
+                let positions = policy.parse::<PositionsPolicy>().unwrap();
+                if let Some(fix) = positions.fix(password) {
+                    assert!(positions.is_valid(&apply(password, fix)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn generated_lines_round_trip_through_both_policies() {
+        let options = GeneratorOptions {
+            line_count: 1000,
+            seed: 2020,
+            count_range_valid_ratio: 0.3,
+            positions_valid_ratio: 0.6,
+            malformed_ratio: 0.1,
+        };
+        let generated_lines = generate_lines(&options);
+        assert_eq!(generated_lines.len(), 1000);
+        assert_eq!(generate_lines(&options), generated_lines);
+
+        for generated_line in &generated_lines {
+            let count_range = generated_line.line.parse::<PasswordAndCountRangePolicy>();
+            let positions = generated_line.line.parse::<PasswordAndPositionsPolicy>();
+            if generated_line.is_malformed {
+                assert!(count_range.is_err(), "line: {}", generated_line.line);
+                assert!(positions.is_err(), "line: {}", generated_line.line);
+            } else {
+                assert_eq!(
+                    count_range.unwrap().is_valid(),
+                    generated_line.is_valid_under_count_range,
+                    "line: {}",
+                    generated_line.line
+                );
+                assert_eq!(
+                    positions.unwrap().is_valid(),
+                    generated_line.is_valid_under_positions,
+                    "line: {}",
+                    generated_line.line
+                );
+            }
+        }
+
+        let count = |predicate: fn(&GeneratedLine) -> bool| {
+            generated_lines.iter().filter(|&l| predicate(l)).count()
+        };
+        assert!((50..150).contains(&count(|l| l.is_malformed)));
+        assert!((220..320).contains(&count(|l| l.is_valid_under_count_range)));
+        assert!((480..600).contains(&count(|l| l.is_valid_under_positions)));
+    }
+}
@@ -1,8 +1,8 @@
-use clap::{crate_version, App};
-use itertools::Itertools;
-use std::io::{BufReader, Lines};
-use std::ops::Range;
-use std::str::FromStr;
+use clap::{crate_version, App, AppSettings, Arg, ArgMatches, SubCommand};
+use day_2::{
+    compare_policies, count_valid_passwords, generate_lines, validation_report, CompoundPolicy,
+    CountRangePolicy, GeneratorOptions, PolicyAgreement, PositionsPolicy, Segmentation,
+};
 use std::{fs::File, io, io::BufRead, path::Path};
 
 fn main() {
@@ -20,16 +20,69 @@ fn run_app() -> Result<(), String> {
         .version(crate_version!())
         .author("Jonathan Bluett-Duncan <jbluettduncan@gmail.com>")
         .about("Solution for Advent of Code 2020, puzzle 2")
+        .setting(AppSettings::SubcommandsNegateReqs)
+        .setting(AppSettings::ArgsNegateSubcommands)
         .args_from_usage(
             "<INPUT> 'Sets the input file of passwords and their policies to use'
-            --alternative-policy 'Changes policy interpretation according to part 2'",
+            --alternative-policy 'Changes policy interpretation according to part 2'
+            --report 'Lists every line with whether its password is valid and why'
+            --graphemes 'Counts and indexes letters as user-perceived characters (extended grapheme clusters) rather than Unicode code points'",
+        )
+        .arg(
+            Arg::with_name("only-invalid")
+                .long("only-invalid")
+                .requires("report")
+                .help("Lists only the lines with invalid passwords in the report"),
+        )
+        .arg(
+            Arg::with_name("suggest-fixes")
+                .long("suggest-fixes")
+                .requires("report")
+                .help("Suggests the smallest change that would make each invalid password valid"),
+        )
+        .arg(
+            Arg::with_name("compare-policies")
+                .long("compare-policies")
+                .conflicts_with_all(&["alternative-policy", "report"])
+                .help("Counts how many passwords are valid under both, either or neither policy"),
+        )
+        .arg(
+            Arg::with_name("list-lines")
+                .long("list-lines")
+                .requires("compare-policies")
+                .help("Lists the lines in each group when comparing policies"),
+        )
+        .subcommand(
+            SubCommand::with_name("generate")
+                .about("Writes randomly generated passwords and their policies to stdout")
+                .args_from_usage(
+                    "-n, --lines <N> 'How many lines to generate'
+                    --seed [SEED] 'Seeds the generator so that it generates the same lines every time'
+                    --count-range-valid-ratio [RATIO] 'The fraction of passwords that should be valid under the count range policy'
+                    --positions-valid-ratio [RATIO] 'The fraction of passwords that should be valid under the positions policy'
+                    --malformed-ratio [RATIO] 'The fraction of lines that should be malformed'",
+                ),
         )
         .get_matches();
 
+    if let Some(generate_matches) = matches.subcommand_matches("generate") {
+        return generate(generate_matches);
+    }
+
     let passwords_and_policies_file = matches.value_of("INPUT").unwrap();
     let alternative_policy = matches.is_present("alternative-policy");
+    let report = matches.is_present("report");
+    let only_invalid = matches.is_present("only-invalid");
+    let suggest_fixes = matches.is_present("suggest-fixes");
+    let compare = matches.is_present("compare-policies");
+    let list_lines = matches.is_present("list-lines");
+    let segmentation = if matches.is_present("graphemes") {
+        Segmentation::Graphemes
+    } else {
+        Segmentation::Chars
+    };
 
-    let lines = match read_lines(&passwords_and_policies_file) {
+    let lines = match read_lines(passwords_and_policies_file) {
         Ok(lines) => lines,
         Err(_) => {
             return Err(format!(
@@ -39,429 +92,132 @@ fn run_app() -> Result<(), String> {
         }
     };
 
-    if alternative_policy {
-        print_invalid_passwords_by_positions_policy(lines)?;
-    } else {
-        print_invalid_passwords_by_count_range_policy(lines)?;
-    }
-
-    Ok(())
-}
-
-fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
-where
-    P: AsRef<Path>,
-{
-    let file = File::open(filename)?;
-    Ok(io::BufReader::new(file).lines())
-}
-
-fn print_invalid_passwords_by_count_range_policy(
-    lines: Lines<BufReader<File>>,
-) -> Result<(), String> {
-    let mut passwords_and_policies = Vec::new();
-    for (index, line) in lines.enumerate() {
-        let line = match line {
-            Ok(line) => line,
-            Err(_) => return Err(format!("Line {} cannot be read.", index)),
-        };
-
-        let line = line.trim();
-        if !line.is_empty() {
-            passwords_and_policies.push(match PasswordAndCountRangePolicy::from_str(line) {
-                Ok(p) => p,
-                Err(err) => {
-                    return Err(format!(
-                        r#"Line {} is not a password-and-policy string: "{}". Reason: {}."#,
-                        index, line, err
-                    ));
+    if compare {
+        let compared_lines =
+            compare_policies(lines, segmentation).map_err(|err| err.to_string())?;
+        for (agreement, description) in &[
+            (PolicyAgreement::ValidUnderBoth, "Valid under both policies"),
+            (
+                PolicyAgreement::ValidOnlyUnderCountRange,
+                "Valid only under the count range policy",
+            ),
+            (
+                PolicyAgreement::ValidOnlyUnderPositions,
+                "Valid only under the positions policy",
+            ),
+            (
+                PolicyAgreement::ValidUnderNeither,
+                "Valid under neither policy",
+            ),
+        ] {
+            let lines_in_group = compared_lines
+                .iter()
+                .filter(|compared_line| compared_line.agreement == *agreement)
+                .collect::<Vec<_>>();
+            println!("{}: {}", description, lines_in_group.len());
+            if list_lines {
+                for compared_line in lines_in_group {
+                    println!(
+                        r#"    Line {} "{}""#,
+                        compared_line.line_number, compared_line.line
+                    );
                 }
-            });
-        }
-    }
-
-    let invalid_passwords_count = passwords_and_policies
-        .iter()
-        .filter(|&p| p.is_valid())
-        .count();
-    println!("{:?}", invalid_passwords_count);
-
-    Ok(())
-}
-
-fn print_invalid_passwords_by_positions_policy(
-    lines: Lines<BufReader<File>>,
-) -> Result<(), String> {
-    let mut passwords_and_policies = Vec::new();
-    for (index, line) in lines.enumerate() {
-        let line = match line {
-            Ok(line) => line,
-            Err(_) => return Err(format!("Line {} cannot be read.", index)),
-        };
-
-        let line = line.trim();
-        if !line.is_empty() {
-            passwords_and_policies.push(match PasswordAndPositionsPolicy::from_str(line) {
-                Ok(p) => p,
-                Err(err) => {
-                    return Err(format!(
-                        r#"Line {} is not a password-and-policy string: "{}". Reason: {}."#,
-                        index, line, err
-                    ));
-                }
-            });
-        }
-    }
-
-    let invalid_passwords_count = passwords_and_policies
-        .iter()
-        .filter(|&p| p.is_valid())
-        .count();
-    println!("{:?}", invalid_passwords_count);
-
-    Ok(())
-}
-
-#[derive(Debug, PartialEq, Eq, Hash)]
-struct PasswordAndCountRangePolicy {
-    password: String,
-    policy: CountRangePolicy,
-}
-
-impl PasswordAndCountRangePolicy {
-    // fn factory(alternative_policy: bool) -> fn(input: &str) -> Result<PasswordAndCountRangePolicy, String> {
-    //     fn count_range_policy(s: &str) -> Result<PasswordAndCountRangePolicy, String> {
-    //         todo!()
-    //         // return s.parse::<CountRangePolicy>();
-    //     }
-    //
-    //     fn position_policy(s: &str) -> Result<PasswordAndCountRangePolicy, String> {
-    //         todo!()
-    //     }
-    //
-    //     return if alternative_policy {
-    //         |s: &str| {
-    //             count_range_policy(s)
-    //         }
-    //     } else {
-    //         |s: &str| {
-    //             count_range_policy(s)
-    //         }
-    //     }
-    // }
-
-    fn is_valid(&self) -> bool {
-        let expected_letter_count = &self
-            .password
-            .chars()
-            .filter(|&c| c == self.policy.expected_letter)
-            .count();
-        self.policy
-            .expected_letter_count_range
-            .contains(expected_letter_count)
-    }
-}
-
-impl FromStr for PasswordAndCountRangePolicy {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let parts = s.split(": ").map(|p| p.trim()).collect_vec();
-        if parts.len() != 2 {
-            return Err(
-                r#"Expected to follow pattern "<digit>-<digit>: <letter>: <password>""#.to_string(),
-            );
-        }
-
-        let policy = match parts[0].parse::<CountRangePolicy>() {
-            Ok(policy) => policy,
-            Err(_) => {
-                return Err(
-                    r#"Expected to follow pattern "<digit>-<digit>: <letter>: <password>""#
-                        .to_string(),
-                );
             }
-        };
-
-        Ok(PasswordAndCountRangePolicy {
-            password: parts[1].to_string(),
-            policy,
-        })
+        }
+        return Ok(());
     }
-}
-
-// trait Policy {
-//     fn letter(&self) -> char;
-//     fn first_digit(&self) -> usize;
-//     fn second_digit(&self) -> usize;
-// }
 
-#[derive(Debug, PartialEq, Eq, Hash)]
-struct CountRangePolicy {
-    expected_letter: char,
-    expected_letter_count_range: Range<usize>,
-}
-
-impl FromStr for CountRangePolicy {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let parts: Vec<&str> = s.split(' ').collect();
-        if parts.len() != 2 {
-            return Err(
-                r#"Expected to follow pattern "<digit>-<digit>: <letter>: <password>""#.to_string(),
-            );
+    if report {
+        let report_lines = if alternative_policy {
+            validation_report::<CompoundPolicy<PositionsPolicy>, _>(lines, segmentation)
+        } else {
+            validation_report::<CompoundPolicy<CountRangePolicy>, _>(lines, segmentation)
         }
-
-        let expected_letter = match parts[1].parse::<char>() {
-            Ok(letter) => letter,
-            Err(_) => {
-                return Err(
-                    r#"Expected to follow pattern "<digit>-<digit>: <letter>: <password>""#
-                        .to_string(),
-                );
-            }
-        };
-
-        let digits: Result<Vec<usize>, _> = parts[0]
-            .split(|c| c == ' ' || c == '-')
-            .map(|part| part.parse::<usize>())
-            .collect();
-        let digits = match digits {
-            Ok(p) => p,
-            Err(_) => {
-                return Err(
-                    r#"Expected to follow pattern "<digit>-<digit>: <letter>: <password>""#
-                        .to_string(),
-                );
-            }
-        };
-        if digits.len() != 2 {
-            return Err(
-                r#"Expected to follow pattern "<digit>-<digit>: <letter>: <password>""#.to_string(),
+        .map_err(|err| err.to_string())?;
+        for report_line in report_lines
+            .iter()
+            .filter(|report_line| !only_invalid || !report_line.is_valid)
+        {
+            let fix = match &report_line.fix {
+                Some(fix) if suggest_fixes => format!("; to fix, {}", fix),
+                _ => String::new(),
+            };
+            println!(
+                r#"Line {} "{}" is {}: {}{}"#,
+                report_line.line_number,
+                report_line.line,
+                if report_line.is_valid {
+                    "valid"
+                } else {
+                    "invalid"
+                },
+                report_line.reason,
+                fix
             );
         }
-        let expected_letter_count_range = Range {
-            start: digits[0],
-            end: digits[1] + 1,
-        };
-
-        Ok(CountRangePolicy {
-            expected_letter,
-            expected_letter_count_range,
-        })
+        return Ok(());
     }
-}
 
-#[derive(Debug, PartialEq, Eq, Hash)]
-struct PasswordAndPositionsPolicy {
-    password: String,
-    policy: PositionsPolicy,
-}
-
-impl PasswordAndPositionsPolicy {
-    fn is_valid(&self) -> bool {
-        let password_first_letter = &self
-            .password
-            .chars()
-            .nth(self.policy.first_expected_position - 1);
-        let password_second_letter = &self
-            .password
-            .chars()
-            .nth(self.policy.second_expected_position - 1);
-        return password_first_letter
-            .map(|l| l == self.policy.expected_letter)
-            .unwrap_or(false)
-            ^ password_second_letter
-                .map(|l| l == self.policy.expected_letter)
-                .unwrap_or(false);
+    let valid_passwords_count = if alternative_policy {
+        count_valid_passwords::<CompoundPolicy<PositionsPolicy>, _>(lines, segmentation)
+    } else {
+        count_valid_passwords::<CompoundPolicy<CountRangePolicy>, _>(lines, segmentation)
     }
-}
+    .map_err(|err| err.to_string())?;
+    println!("{}", valid_passwords_count);
 
-impl FromStr for PasswordAndPositionsPolicy {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let parts = s.split(": ").map(|p| p.trim()).collect_vec();
-        if parts.len() != 2 {
-            return Err(
-                r#"Expected to follow pattern "<digit>-<digit>: <letter>: <password>""#.to_string(),
-            );
-        }
-
-        let policy = match parts[0].parse::<PositionsPolicy>() {
-            Ok(policy) => policy,
-            Err(_) => {
-                return Err(
-                    r#"Expected to follow pattern "<digit>-<digit>: <letter>: <password>""#
-                        .to_string(),
-                );
-            }
-        };
-
-        Ok(PasswordAndPositionsPolicy {
-            password: parts[1].to_string(),
-            policy,
-        })
-    }
+    Ok(())
 }
 
-#[derive(Debug, PartialEq, Eq, Hash)]
-struct PositionsPolicy {
-    expected_letter: char,
-    first_expected_position: usize,
-    second_expected_position: usize,
+fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
+where
+    P: AsRef<Path>,
+{
+    let file = File::open(filename)?;
+    Ok(io::BufReader::new(file).lines())
 }
 
-impl FromStr for PositionsPolicy {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let parts: Vec<&str> = s.split(' ').collect();
-        if parts.len() != 2 {
-            return Err(
-                r#"Expected to follow pattern "<digit>-<digit>: <letter>: <password>""#.to_string(),
-            );
-        }
-
-        let expected_letter = match parts[1].parse::<char>() {
-            Ok(letter) => letter,
-            Err(_) => {
-                return Err(
-                    r#"Expected to follow pattern "<digit>-<digit>: <letter>: <password>""#
-                        .to_string(),
-                );
-            }
-        };
-
-        let digits: Result<Vec<usize>, _> = parts[0]
-            .split(|c| c == ' ' || c == '-')
-            .map(|part| part.parse::<usize>())
-            .collect();
-        let digits = match digits {
-            Ok(p) => p,
-            Err(_) => {
-                return Err(
-                    r#"Expected to follow pattern "<digit>-<digit>: <letter>: <password>""#
-                        .to_string(),
-                );
-            }
-        };
-        if digits.len() != 2 {
-            return Err(
-                r#"Expected to follow pattern "<digit>-<digit>: <letter>: <password>""#.to_string(),
-            );
+fn generate(matches: &ArgMatches) -> Result<(), String> {
+    let line_count = matches
+        .value_of("lines")
+        .unwrap()
+        .parse::<usize>()
+        .map_err(|_| "The number of lines to generate is not a whole non-negative number.")?;
+    let seed = match matches.value_of("seed") {
+        Some(seed) => seed
+            .parse::<u64>()
+            .map_err(|_| "The seed is not a whole non-negative number.")?,
+        None => rand::random(),
+    };
+    let ratio = |name: &str, description: &str| -> Result<f64, String> {
+        let ratio = matches.value_of(name).unwrap_or("0.5");
+        match ratio.parse::<f64>() {
+            Ok(ratio) if (0.0..=1.0).contains(&ratio) => Ok(ratio),
+            _ => Err(format!(
+                "The fraction of {} is not a number from 0 to 1.",
+                description
+            )),
         }
+    };
+    let options = GeneratorOptions {
+        line_count,
+        seed,
+        count_range_valid_ratio: ratio(
+            "count-range-valid-ratio",
+            "passwords valid under the count range policy",
+        )?,
+        positions_valid_ratio: ratio(
+            "positions-valid-ratio",
+            "passwords valid under the positions policy",
+        )?,
+        malformed_ratio: match matches.value_of("malformed-ratio") {
+            Some(_) => ratio("malformed-ratio", "malformed lines")?,
+            None => 0.0,
+        },
+    };
 
-        Ok(PositionsPolicy {
-            expected_letter,
-            first_expected_position: digits[0],
-            second_expected_position: digits[1],
-        })
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn count_range_policy_from_str() {
-        let policy = "1-3 a".parse::<CountRangePolicy>();
-        assert!(policy.is_ok());
-        let policy = policy.unwrap();
-        assert_eq!(policy.expected_letter, 'a');
-        assert_eq!(policy.expected_letter_count_range, (1..4));
-    }
-
-    #[test]
-    fn positions_policy_from_str() {
-        let policy = "1-3 a".parse::<PositionsPolicy>();
-        assert!(policy.is_ok());
-        let policy = policy.unwrap();
-        assert_eq!(policy.expected_letter, 'a');
-        assert_eq!(policy.first_expected_position, 1);
-        assert_eq!(policy.second_expected_position, 3);
-    }
-
-    #[test]
-    fn password_and_count_range_policy_from_str() {
-        let password_and_policy = "1-3 a: abcde".parse::<PasswordAndCountRangePolicy>();
-        assert!(password_and_policy.is_ok());
-        let password_and_policy = password_and_policy.unwrap();
-        assert_eq!(
-            password_and_policy,
-            PasswordAndCountRangePolicy {
-                password: "abcde".to_string(),
-                policy: CountRangePolicy {
-                    expected_letter: 'a',
-                    expected_letter_count_range: (1..4),
-                },
-            }
-        )
-    }
-
-    #[test]
-    fn password_and_positions_policy_from_str() {
-        let password_and_policy = "1-3 a: abcde".parse::<PasswordAndPositionsPolicy>();
-        assert!(password_and_policy.is_ok());
-        let password_and_policy = password_and_policy.unwrap();
-        assert_eq!(
-            password_and_policy,
-            PasswordAndPositionsPolicy {
-                password: "abcde".to_string(),
-                policy: PositionsPolicy {
-                    expected_letter: 'a',
-                    first_expected_position: 1,
-                    second_expected_position: 3,
-                },
-            }
-        )
-    }
-
-    #[test]
-    fn password_and_count_range_policy_is_valid() {
-        let a = "1-3 a: abcde"
-            .parse::<PasswordAndCountRangePolicy>()
-            .unwrap();
-        assert!(a.is_valid());
-
-        let b = "1-3 b: cdefg"
-            .parse::<PasswordAndCountRangePolicy>()
-            .unwrap();
-        assert!(!b.is_valid());
-
-        let c = "2-9 c: ccccccccc"
-            .parse::<PasswordAndCountRangePolicy>()
-            .unwrap();
-        assert!(c.is_valid());
-    }
-
-    #[test]
-    fn password_and_positions_policy_is_valid() {
-        let a = "1-3 a: abcde"
-            .parse::<PasswordAndPositionsPolicy>()
-            .unwrap();
-        assert!(a.is_valid());
-
-        let b = "1-3 b: bcdef"
-            .parse::<PasswordAndPositionsPolicy>()
-            .unwrap();
-        assert!(b.is_valid());
-
-        let c = "1-3 b: aabcd"
-            .parse::<PasswordAndPositionsPolicy>()
-            .unwrap();
-        assert!(c.is_valid());
-
-        let d = "1-3 b: babcd"
-            .parse::<PasswordAndPositionsPolicy>()
-            .unwrap();
-        assert!(!d.is_valid());
-
-        let e = "2-9 c: ccccccccc"
-            .parse::<PasswordAndPositionsPolicy>()
-            .unwrap();
-        assert!(!e.is_valid());
+    for generated_line in generate_lines(&options) {
+        println!("{}", generated_line.line);
     }
+    Ok(())
 }
@@ -63,4 +63,341 @@ mod given_passwords_and_policies {
             Ok(())
         }
     }
+
+    mod when_day_2_processes_them_and_one_has_a_colon_for_its_letter {
+        use super::*;
+
+        #[test]
+        fn then_it_should_return_number_of_valid_passwords(
+        ) -> Result<(), Box<dyn std::error::Error>> {
+            let mut actual_password_and_policies_file = NamedTempFile::new()?;
+            writeln!(
+                actual_password_and_policies_file,
+                indoc! { "
+                    1-3 :: a:b
+                    1-3 a: abcde
+                " }
+            )?;
+            let expected_result = "2\n";
+
+            let mut cmd = Command::cargo_bin("day-2")?;
+            cmd.arg(actual_password_and_policies_file.path());
+
+            cmd.assert()
+                .success()
+                .stdout(predicate::eq(expected_result));
+            Ok(())
+        }
+    }
+
+    mod when_day_2_processes_them_and_one_has_a_reversed_range {
+        use super::*;
+
+        #[test]
+        fn then_it_should_say_which_part_of_which_line_is_wrong(
+        ) -> Result<(), Box<dyn std::error::Error>> {
+            let mut actual_password_and_policies_file = NamedTempFile::new()?;
+            writeln!(
+                actual_password_and_policies_file,
+                indoc! { "
+                    1-3 a: abcde
+                    3-1 b: cdefg
+                " }
+            )?;
+
+            let mut cmd = Command::cargo_bin("day-2")?;
+            cmd.arg(actual_password_and_policies_file.path());
+
+            cmd.assert().failure().stderr(predicate::str::contains(
+                "Line 1 is not a password-and-policy string: \"3-1 b: cdefg\". \
+                 Reason: the range \"3-1\" at column 1 is reversed; expected \"1-3\".",
+            ));
+            Ok(())
+        }
+    }
+
+    mod when_day_2_processes_them_and_some_have_compound_policies {
+        use super::*;
+
+        #[test]
+        fn then_it_should_return_number_of_valid_passwords(
+        ) -> Result<(), Box<dyn std::error::Error>> {
+            let mut actual_password_and_policies_file = NamedTempFile::new()?;
+            writeln!(
+                actual_password_and_policies_file,
+                indoc! { "
+                    1-3 a: abcde
+                    1-3 a and 2-9 b: abbde
+                    1-3 a and not 2-9 b: abbde
+                    position 1 or 4 is x: abcx
+                " }
+            )?;
+            let expected_result = "3\n";
+
+            let mut cmd = Command::cargo_bin("day-2")?;
+            cmd.arg(actual_password_and_policies_file.path());
+
+            cmd.assert()
+                .success()
+                .stdout(predicate::eq(expected_result));
+            Ok(())
+        }
+    }
+
+    mod when_day_2_reports_on_them {
+        use super::*;
+
+        #[test]
+        fn then_it_should_list_every_line_with_its_verdict_and_reason(
+        ) -> Result<(), Box<dyn std::error::Error>> {
+            let mut actual_password_and_policies_file = NamedTempFile::new()?;
+            writeln!(
+                actual_password_and_policies_file,
+                indoc! { "
+                    1-3 a: abcde
+                    1-3 b: cdefg
+                    2-9 c: ccccccccc
+                " }
+            )?;
+            let expected_result = indoc! { r#"
+                Line 1 "1-3 a: abcde" is valid: letter 'a' occurs 1 time, allowed 1-3
+                Line 2 "1-3 b: cdefg" is invalid: letter 'b' occurs 0 times, allowed 1-3
+                Line 3 "2-9 c: ccccccccc" is valid: letter 'c' occurs 9 times, allowed 2-9
+            "# };
+
+            let mut cmd = Command::cargo_bin("day-2")?;
+            cmd.arg(actual_password_and_policies_file.path())
+                .arg("--report");
+
+            cmd.assert()
+                .success()
+                .stdout(predicate::eq(expected_result));
+            Ok(())
+        }
+
+        #[test]
+        fn then_it_should_list_only_invalid_lines_when_asked(
+        ) -> Result<(), Box<dyn std::error::Error>> {
+            let mut actual_password_and_policies_file = NamedTempFile::new()?;
+            writeln!(
+                actual_password_and_policies_file,
+                indoc! { "
+                    1-3 a: abcde
+                    1-3 b: cdefg
+                    2-9 c: ccccccccc
+                " }
+            )?;
+            let expected_result = indoc! { r#"
+                Line 2 "1-3 b: cdefg" is invalid: neither position 1 nor 3 matches
+                Line 3 "2-9 c: ccccccccc" is invalid: positions 2 and 9 both match
+            "# };
+
+            let mut cmd = Command::cargo_bin("day-2")?;
+            cmd.arg(actual_password_and_policies_file.path())
+                .arg("--alternative-policy")
+                .arg("--report")
+                .arg("--only-invalid");
+
+            cmd.assert()
+                .success()
+                .stdout(predicate::eq(expected_result));
+            Ok(())
+        }
+
+        #[test]
+        fn then_it_should_suggest_how_to_fix_invalid_passwords_when_asked(
+        ) -> Result<(), Box<dyn std::error::Error>> {
+            let mut actual_password_and_policies_file = NamedTempFile::new()?;
+            writeln!(
+                actual_password_and_policies_file,
+                indoc! { "
+                    1-3 a: abcde
+                    1-3 b: cdefg
+                    2-9 c: ccccccccc
+                " }
+            )?;
+            let expected_result = indoc! { r#"
+                Line 1 "1-3 a: abcde" is valid: only position 1 matches
+                Line 2 "1-3 b: cdefg" is invalid: neither position 1 nor 3 matches; to fix, replace position 1 with 'b'
+                Line 3 "2-9 c: ccccccccc" is invalid: positions 2 and 9 both match; to fix, replace position 9 with a letter other than 'c'
+            "# };
+
+            let mut cmd = Command::cargo_bin("day-2")?;
+            cmd.arg(actual_password_and_policies_file.path())
+                .arg("--alternative-policy")
+                .arg("--report")
+                .arg("--suggest-fixes");
+
+            cmd.assert()
+                .success()
+                .stdout(predicate::eq(expected_result));
+            Ok(())
+        }
+    }
+
+    mod when_day_2_compares_both_policies {
+        use super::*;
+
+        #[test]
+        fn then_it_should_count_passwords_by_which_policies_they_satisfy(
+        ) -> Result<(), Box<dyn std::error::Error>> {
+            let mut actual_password_and_policies_file = NamedTempFile::new()?;
+            writeln!(
+                actual_password_and_policies_file,
+                indoc! { "
+                    1-3 a: abcde
+                    1-3 b: cdefg
+                    2-9 c: ccccccccc
+                " }
+            )?;
+            let expected_result = indoc! { "
+                Valid under both policies: 1
+                Valid only under the count range policy: 1
+                Valid only under the positions policy: 0
+                Valid under neither policy: 1
+            " };
+
+            let mut cmd = Command::cargo_bin("day-2")?;
+            cmd.arg(actual_password_and_policies_file.path())
+                .arg("--compare-policies");
+
+            cmd.assert()
+                .success()
+                .stdout(predicate::eq(expected_result));
+            Ok(())
+        }
+
+        #[test]
+        fn then_it_should_list_lines_by_which_policies_they_satisfy_when_asked(
+        ) -> Result<(), Box<dyn std::error::Error>> {
+            let mut actual_password_and_policies_file = NamedTempFile::new()?;
+            writeln!(
+                actual_password_and_policies_file,
+                indoc! { "
+                    1-3 a: abcde
+                    1-3 b: cdefg
+                    2-9 c: ccccccccc
+                " }
+            )?;
+            let expected_result = indoc! { r#"
+                Valid under both policies: 1
+                    Line 1 "1-3 a: abcde"
+                Valid only under the count range policy: 1
+                    Line 3 "2-9 c: ccccccccc"
+                Valid only under the positions policy: 0
+                Valid under neither policy: 1
+                    Line 2 "1-3 b: cdefg"
+            "# };
+
+            let mut cmd = Command::cargo_bin("day-2")?;
+            cmd.arg(actual_password_and_policies_file.path())
+                .arg("--compare-policies")
+                .arg("--list-lines");
+
+            cmd.assert()
+                .success()
+                .stdout(predicate::eq(expected_result));
+            Ok(())
+        }
+    }
+
+    mod when_day_2_compares_both_policies_on_compound_policies {
+        use super::*;
+
+        #[test]
+        fn then_it_reads_every_clause_under_each_policy_in_turn(
+        ) -> Result<(), Box<dyn std::error::Error>> {
+            let mut actual_password_and_policies_file = NamedTempFile::new()?;
+            writeln!(
+                actual_password_and_policies_file,
+                indoc! { "
+                    1-3 a and 2-9 b: abbde
+                    1-3 a or position 9 is x: bacde
+                    not 2-9 c: ccccccccc
+                " }
+            )?;
+            let expected_result = indoc! { "
+                Valid under both policies: 1
+                Valid only under the count range policy: 1
+                Valid only under the positions policy: 1
+                Valid under neither policy: 0
+            " };
+
+            let mut cmd = Command::cargo_bin("day-2")?;
+            cmd.arg(actual_password_and_policies_file.path())
+                .arg("--compare-policies");
+
+            cmd.assert()
+                .success()
+                .stdout(predicate::eq(expected_result));
+            Ok(())
+        }
+    }
+
+    mod when_day_2_processes_them_by_grapheme {
+        use super::*;
+
+        #[test]
+        fn then_it_should_count_and_index_user_perceived_characters(
+        ) -> Result<(), Box<dyn std::error::Error>> {
+            let mut actual_password_and_policies_file = NamedTempFile::new()?;
+            writeln!(
+                actual_password_and_policies_file,
+                "1-1 e: e\u{301}xe\u{301}e\n\
+                 2-2 e\u{301}: e\u{301}xe\u{301}e"
+            )?;
+            let expected_result = "2\n";
+
+            let mut cmd = Command::cargo_bin("day-2")?;
+            cmd.arg(actual_password_and_policies_file.path())
+                .arg("--graphemes");
+
+            cmd.assert()
+                .success()
+                .stdout(predicate::eq(expected_result));
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod given_a_seed {
+    use super::*;
+
+    mod when_day_2_generates_passwords_and_policies {
+        use super::*;
+
+        #[test]
+        fn then_it_should_write_the_same_parseable_lines_every_time(
+        ) -> Result<(), Box<dyn std::error::Error>> {
+            let generate = || -> Result<String, Box<dyn std::error::Error>> {
+                let output = Command::cargo_bin("day-2")?
+                    .arg("generate")
+                    .arg("--lines")
+                    .arg("100")
+                    .arg("--seed")
+                    .arg("2020")
+                    .arg("--count-range-valid-ratio")
+                    .arg("0.25")
+                    .output()?;
+                Ok(String::from_utf8(output.stdout)?)
+            };
+            let generated_lines = generate()?;
+            assert_eq!(generated_lines.lines().count(), 100);
+            assert_eq!(generate()?, generated_lines);
+
+            let mut actual_password_and_policies_file = NamedTempFile::new()?;
+            write!(actual_password_and_policies_file, "{}", generated_lines)?;
+
+            let mut cmd = Command::cargo_bin("day-2")?;
+            cmd.arg(actual_password_and_policies_file.path());
+
+            cmd.assert()
+                .success()
+                .stdout(predicate::function(|count: &str| {
+                    matches!(count.trim().parse::<usize>(), Ok(10..=40))
+                }));
+            Ok(())
+        }
+    }
 }
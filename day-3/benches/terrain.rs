@@ -0,0 +1,75 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use day_3::{PackedTreeMap, Slope, TreeMap};
+use itertools::Itertools;
+
+// A square map with trees scattered in a fixed pattern, so that every run sees the same map
+fn generated_map(side: usize) -> String {
+    (0..side)
+        .map(|row| {
+            (0..side)
+                .map(|column| {
+                    if (row * 7919 + column * 104_729) % 11 < 3 {
+                        '#'
+                    } else {
+                        '.'
+                    }
+                })
+                .collect::<String>()
+        })
+        .join("\n")
+}
+
+const SIDES: [usize; 3] = [1000, 2000, 4000];
+
+fn bench_parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse map");
+    group.sample_size(10);
+    for &side in &SIDES {
+        let map = generated_map(side);
+        let cells = side * side;
+
+        let tree_map = map.parse::<TreeMap>().unwrap();
+        let packed_tree_map = map.parse::<PackedTreeMap>().unwrap();
+        println!(
+            "{} cells: 2D char array takes {} bytes, packed bitset takes {} bytes",
+            cells,
+            tree_map.size_in_bytes(),
+            packed_tree_map.size_in_bytes()
+        );
+
+        group.bench_with_input(BenchmarkId::new("2D char array", cells), &map, |b, map| {
+            b.iter(|| map.parse::<TreeMap>().unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("packed bitset", cells), &map, |b, map| {
+            b.iter(|| map.parse::<PackedTreeMap>().unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn bench_trees_on_slope(c: &mut Criterion) {
+    let slope = Slope::new(3, 1).unwrap();
+
+    let mut group = c.benchmark_group("trees on slope");
+    for &side in &SIDES {
+        let map = generated_map(side);
+        let cells = side * side;
+        let tree_map = map.parse::<TreeMap>().unwrap();
+        let packed_tree_map = map.parse::<PackedTreeMap>().unwrap();
+
+        group.bench_with_input(
+            BenchmarkId::new("2D char array", cells),
+            &tree_map,
+            |b, tree_map| b.iter(|| tree_map.trees_on_slope(slope)),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("packed bitset", cells),
+            &packed_tree_map,
+            |b, packed_tree_map| b.iter(|| packed_tree_map.trees_on_slope(slope)),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_parse, bench_trees_on_slope);
+criterion_main!(benches);
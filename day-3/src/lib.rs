@@ -1,77 +1,687 @@
-use itertools::{iterate, Itertools};
+use itertools::iterate;
 use ndarray::Array2;
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use std::fmt;
+use std::mem;
+use std::str::FromStr;
 use thiserror::Error;
 
 pub fn trees_encountered(map: &str) -> Result<u32, Day3Error> {
-    trees_encountered_impl(map, Direction { right: 3, down: 1 })
+    trees_encountered_in_direction(map, Direction { right: 3, down: 1 })
 }
 
 pub fn trees_encountered_in_direction(map: &str, direction: Direction) -> Result<u32, Day3Error> {
-    trees_encountered_impl(map, direction)
+    let slope = Slope::try_from(direction)?;
+    Ok(map.parse::<TreeMap>()?.trees_on_slope(slope))
 }
 
-fn trees_encountered_impl(map: &str, direction: Direction) -> Result<u32, Day3Error> {
-    if map.contains(|c: char| c != '.' && c != '#' && c != '\n') {
-        todo!()
+// A validated map of open squares ('.') and trees ('#'), or of whichever tiles its legend has,
+// that repeats forever to the right. Any tile with a collision weight counts as a tree, as many
+// times over as its weight, so with the default legend only '#' squares are trees.
+#[derive(Debug, PartialEq, Clone)]
+pub struct TreeMap {
+    map: Array2<char>,
+    legend: Legend,
+}
+
+impl TreeMap {
+    pub fn with_legend(map: &str, legend: Legend) -> Result<TreeMap, Day3Error> {
+        validate(map, &legend)?;
+        Ok(TreeMap {
+            map: to_2d_array(map)?,
+            legend,
+        })
     }
 
-    if !is_rectangular(map) {
-        todo!()
+    pub fn legend(&self) -> &Legend {
+        &self.legend
     }
 
-    let map = to_2d_array(map)?;
+    pub fn width(&self) -> usize {
+        self.map.ncols()
+    }
 
-    struct Point {
-        row: usize,
-        column: usize,
+    pub fn height(&self) -> usize {
+        self.map.nrows()
+    }
+
+    // Whether there's a tree at the given 0-based row and column, where columns past the right
+    // edge wrap around to the left. Panics if `row` is not less than the height.
+    pub fn is_tree(&self, row: usize, column: usize) -> bool {
+        self.collision_weight(row, column) > 0
+    }
+
+    // The tile at the given 0-based row and column, wrapping columns as `is_tree` does
+    pub fn tile(&self, row: usize, column: usize) -> Option<&Tile> {
+        self.legend.tile(self.map[(row, column % self.width())])
+    }
+
+    // What running into the square at the given 0-based row and column costs, wrapping columns as
+    // `is_tree` does
+    pub fn collision_weight(&self, row: usize, column: usize) -> u32 {
+        self.legend
+            .collision_weight(self.map[(row, column % self.width())])
+    }
+
+    pub fn trees_on_slope(&self, slope: Slope) -> u32 {
+        points_on_slope(self.height(), slope)
+            .map(|point| self.legend.collision_weight(self.square_at(&point)))
+            .fold(0u32, |acc, i| acc.saturating_add(i))
+    }
+
+    // Counts each tile in the legend that the path down `slope` visits, and totals their
+    // collision weights
+    pub fn tiles_on_slope(&self, slope: Slope) -> TilesEncountered {
+        let mut counts = self
+            .legend
+            .tiles()
+            .iter()
+            .map(|tile| TileCount {
+                tile: tile.clone(),
+                count: 0,
+            })
+            .collect::<Vec<_>>();
+        for point in points_on_slope(self.height(), slope) {
+            let square = self.square_at(&point);
+            if let Some(tile_count) = counts
+                .iter_mut()
+                .find(|tile_count| tile_count.tile.symbol == square)
+            {
+                tile_count.count = tile_count.count.saturating_add(1);
+            }
+        }
+        let weighted_total = counts
+            .iter()
+            .map(|tile_count| {
+                tile_count
+                    .count
+                    .saturating_mul(tile_count.tile.collision_weight)
+            })
+            .fold(0u32, |acc, i| acc.saturating_add(i));
+        TilesEncountered {
+            counts,
+            weighted_total,
+        }
+    }
+
+    // Tries every slope going right 0 to `max_right` and down 1 to `max_down`, and ranks them by
+    // the trees encountered on each. Slopes with the same number of trees are ranked by step size:
+    // first by how far they go right and down in total, then by how far down.
+    pub fn rank_slopes(
+        &self,
+        max_right: u32,
+        max_down: u32,
+        ranking: SlopeRanking,
+    ) -> Vec<RankedSlope> {
+        let mut ranked_slopes = (1..=max_down)
+            .flat_map(|down| (0..=max_right).map(move |right| (right, down)))
+            .map(|(right, down)| {
+                let slope = Slope {
+                    right: i64::from(right),
+                    down: down as usize,
+                };
+                RankedSlope {
+                    slope,
+                    trees_encountered: self.trees_on_slope(slope),
+                }
+            })
+            .collect::<Vec<_>>();
+        ranked_slopes.sort_by_key(|ranked_slope| {
+            let slope = ranked_slope.slope;
+            let trees_encountered = match ranking {
+                SlopeRanking::FewestTrees => i64::from(ranked_slope.trees_encountered),
+                SlopeRanking::MostTrees => -i64::from(ranked_slope.trees_encountered),
+            };
+            (
+                trees_encountered,
+                slope.right + slope.down as i64,
+                slope.down,
+            )
+        });
+        ranked_slopes
+    }
+
+    // Draws the map repeated side by side as many times as the path down `slope` needs, with 'X'
+    // at each square the path visits that has a collision weight, like a tree, and 'O' at every
+    // other square it visits. With `use_colour`, the visited squares are coloured with ANSI escape
+    // codes: red for 'X', green for 'O'.
+    pub fn render_slope(&self, slope: Slope, use_colour: bool) -> String {
+        let width = self.width() as i64;
+        let visited_points = points_on_slope(self.height(), slope)
+            .map(|point| (point.row, point.column))
+            .collect::<HashSet<_>>();
+        let first_tile = visited_points
+            .iter()
+            .map(|&(_, column)| column.div_euclid(width))
+            .min()
+            .unwrap_or(0);
+        let last_tile = visited_points
+            .iter()
+            .map(|&(_, column)| column.div_euclid(width))
+            .max()
+            .unwrap_or(0);
+
+        let mut result = String::new();
+        for row in 0..self.height() {
+            for column in first_tile * width..(last_tile + 1) * width {
+                let square = self.square_at(&Point { row, column });
+                let (square, colour) = if !visited_points.contains(&(row, column)) {
+                    (square, None)
+                } else if self.legend.collision_weight(square) > 0 {
+                    ('X', Some(RED))
+                } else {
+                    ('O', Some(GREEN))
+                };
+                match colour {
+                    Some(colour) if use_colour => {
+                        result.push_str(&format!("{}{}{}", colour, square, RESET))
+                    }
+                    _ => result.push(square),
+                }
+            }
+            result.push('\n');
+        }
+        result
+    }
+
+    fn square_at(&self, point: &Point) -> char {
+        let width = self.width() as i64;
+        self.map[(point.row, point.column.rem_euclid(width) as usize)]
+    }
+
+    // How much memory the squares take up
+    pub fn size_in_bytes(&self) -> usize {
+        self.map.len() * mem::size_of::<char>()
+    }
+
+    // Finds the route from the top-left corner off the bottom of the map that encounters the
+    // fewest trees, where each step may be any one of `moves`. Returns `None` if there are no
+    // moves. Routes that encounter as many trees as each other are preferred by where they leave
+    // the map, top-most then left-most, and then by the order of `moves`.
+    pub fn fewest_trees_route(&self, moves: &[Slope]) -> Option<Route> {
+        if moves.is_empty() {
+            return None;
+        }
+
+        let width = self.width();
+        let height = self.height();
+
+        // The fewest trees encountered on the way to each square, and the square and move that
+        // got there
+        let mut best = vec![vec![None::<(u32, Option<(usize, usize, Slope)>)>; width]; height];
+        best[0][0] = Some((self.collision_weight(0, 0), None));
+        let mut last_square = None::<(u32, usize, usize)>;
+        for row in 0..height {
+            for column in 0..width {
+                let trees_encountered = match best[row][column] {
+                    Some((trees_encountered, _)) => trees_encountered,
+                    None => continue,
+                };
+                for &next_move in moves {
+                    let next_row = row + next_move.down;
+                    if next_row >= height {
+                        let is_fewer_trees = match last_square {
+                            Some((trees, _, _)) => trees_encountered < trees,
+                            None => true,
+                        };
+                        if is_fewer_trees {
+                            last_square = Some((trees_encountered, row, column));
+                        }
+                        continue;
+                    }
+                    let next_column =
+                        (column as i64 + next_move.right).rem_euclid(width as i64) as usize;
+                    let next_trees_encountered = trees_encountered
+                        .saturating_add(self.collision_weight(next_row, next_column));
+                    let next_best = &mut best[next_row][next_column];
+                    let is_fewer_trees = match next_best {
+                        Some((trees, _)) => next_trees_encountered < *trees,
+                        None => true,
+                    };
+                    if is_fewer_trees {
+                        *next_best = Some((next_trees_encountered, Some((row, column, next_move))));
+                    }
+                }
+            }
+        }
+
+        let (trees_encountered, mut row, mut column) = last_square?;
+        let mut squares = vec![(row, column)];
+        let mut route_moves = Vec::new();
+        while let Some((_, Some((previous_row, previous_column, previous_move)))) =
+            best[row][column]
+        {
+            squares.push((previous_row, previous_column));
+            route_moves.push(previous_move);
+            row = previous_row;
+            column = previous_column;
+        }
+        squares.reverse();
+        route_moves.reverse();
+        Some(Route {
+            squares,
+            moves: route_moves,
+            trees_encountered,
+        })
     }
+}
 
-    let width = map.ncols();
-    let height = map.nrows();
-    let next_point = |point: &Point| Point {
-        row: point.row + (direction.down as usize),
-        column: (point.column + (direction.right as usize)) % width,
+// A route down a map, from its top-left corner to the last square before it leaves the bottom
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Route {
+    // The 0-based row and column of each square visited, with columns wrapped to within the map
+    pub squares: Vec<(usize, usize)>,
+    // The move taken from each square to the next
+    pub moves: Vec<Slope>,
+    pub trees_encountered: u32,
+}
+
+// Parses the moves allowed on a route, written as "RIGHT,DOWN" for a single move, or as
+// "FIRST..LAST,DOWN" for every move going right from FIRST to LAST inclusive, e.g. "1..3,1"
+pub fn parse_moves(s: &str) -> Result<Vec<Slope>, Day3Error> {
+    let invalid_moves = || Day3Error::InvalidMoves {
+        moves: s.to_string(),
     };
-    let points =
-        iterate(Point { row: 0, column: 0 }, next_point).take_while(|point| point.row < height);
-    let result = points
-        .map(|point| map[(point.row, point.column)])
-        .filter(|part| *part == '#')
-        .map(|_| 1u32)
-        .fold(0u32, |acc, i| acc.saturating_add(i));
-    Ok(result)
+    let (rights, down) = parse_steps::<String, usize>(s).map_err(|_| invalid_moves())?;
+    let mut rights_range = rights.splitn(2, "..").map(str::trim);
+    let first = rights_range.next().unwrap_or("");
+    let last = rights_range.next().unwrap_or(first);
+    let first = first.parse::<i64>().map_err(|_| invalid_moves())?;
+    let last = last.parse::<i64>().map_err(|_| invalid_moves())?;
+    if first > last {
+        return Err(invalid_moves());
+    }
+    (first..=last)
+        .map(|right| Slope::new(right, down))
+        .collect()
+}
+
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const RESET: &str = "\x1b[0m";
+
+impl FromStr for TreeMap {
+    type Err = Day3Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        TreeMap::with_legend(s, Legend::default())
+    }
+}
+
+// A kind of square that a map may have, and how much it costs to run into one
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Tile {
+    pub symbol: char,
+    pub name: String,
+    pub collision_weight: u32,
+}
+
+impl FromStr for Tile {
+    type Err = Day3Error;
+
+    // Parses a tile written as "SYMBOL,NAME,WEIGHT", e.g. "^,rock,2"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid_tile = || Day3Error::InvalidTile {
+            tile: s.to_string(),
+        };
+        let mut parts = s.splitn(3, ',');
+        let mut symbol = parts.next().ok_or_else(invalid_tile)?.chars();
+        let name = parts.next().ok_or_else(invalid_tile)?;
+        let collision_weight = parts.next().ok_or_else(invalid_tile)?;
+        match (symbol.next(), symbol.next()) {
+            (Some(symbol), None) if !name.is_empty() => Ok(Tile {
+                symbol,
+                name: name.to_string(),
+                collision_weight: collision_weight.parse().map_err(|_| invalid_tile())?,
+            }),
+            _ => Err(invalid_tile()),
+        }
+    }
+}
+
+// The tiles that a map may have. The default legend has just open squares ('.'), which cost
+// nothing to run into, and trees ('#'), which cost 1 each.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Legend {
+    tiles: Vec<Tile>,
+}
+
+impl Legend {
+    // Adds `tile` to the legend, replacing any tile that has the same symbol
+    pub fn with_tile(mut self, tile: Tile) -> Legend {
+        match self.tiles.iter_mut().find(|t| t.symbol == tile.symbol) {
+            Some(existing_tile) => *existing_tile = tile,
+            None => self.tiles.push(tile),
+        }
+        self
+    }
+
+    pub fn tiles(&self) -> &[Tile] {
+        &self.tiles
+    }
+
+    pub fn tile(&self, symbol: char) -> Option<&Tile> {
+        self.tiles.iter().find(|tile| tile.symbol == symbol)
+    }
+
+    pub fn collision_weight(&self, symbol: char) -> u32 {
+        self.tile(symbol).map_or(0, |tile| tile.collision_weight)
+    }
+}
+
+impl Default for Legend {
+    fn default() -> Self {
+        Legend {
+            tiles: vec![
+                Tile {
+                    symbol: '.',
+                    name: "open".to_string(),
+                    collision_weight: 0,
+                },
+                Tile {
+                    symbol: '#',
+                    name: "tree".to_string(),
+                    collision_weight: 1,
+                },
+            ],
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TileCount {
+    pub tile: Tile,
+    pub count: u32,
+}
+
+// How many of each tile in a legend a path visits, in the legend's order, and the sum of their
+// collision weights. With the default legend, the weighted total is the number of trees.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TilesEncountered {
+    pub counts: Vec<TileCount>,
+    pub weighted_total: u32,
+}
+
+struct Point {
+    row: usize,
+    column: i64,
+}
+
+// Every point visited from the top-left corner to the bottom of a map `height` rows tall. Columns
+// are not wrapped, so they may go past either edge of the map.
+fn points_on_slope(height: usize, slope: Slope) -> impl Iterator<Item = Point> {
+    let next_point = move |point: &Point| Point {
+        row: point.row + slope.down,
+        column: point.column + slope.right,
+    };
+    iterate(Point { row: 0, column: 0 }, next_point).take_while(move |point| point.row < height)
+}
+
+// The same map as `TreeMap`, but with each row packed into bits, one per square, so that it takes
+// up 1 bit per square rather than the 4 bytes of a `char`
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct PackedTreeMap {
+    width: usize,
+    height: usize,
+    words_per_row: usize,
+    words: Vec<u64>,
+}
+
+const BITS_PER_WORD: usize = 64;
+
+impl PackedTreeMap {
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    // Wraps `column` into the row, then reads bit `column % 64` of word `column / 64` of that
+    // row's words. A row off the bottom would only fail on the `words` index, so it's asserted
+    // first to panic with the row rather than a flat index.
+    pub fn is_tree(&self, row: usize, column: usize) -> bool {
+        assert!(row < self.height, "row {} is off the map", row);
+        self.is_tree_in_bounds(row, column % self.width)
+    }
+
+    pub fn trees_on_slope(&self, slope: Slope) -> u32 {
+        let width = self.width as i64;
+        points_on_slope(self.height, slope)
+            .filter(|point| {
+                self.is_tree_in_bounds(point.row, point.column.rem_euclid(width) as usize)
+            })
+            .map(|_| 1u32)
+            .fold(0u32, |acc, i| acc.saturating_add(i))
+    }
+
+    // The packed words, where each row is padded out to a whole number of `u64`s
+    pub fn size_in_bytes(&self) -> usize {
+        self.words.len() * mem::size_of::<u64>()
+    }
+
+    fn is_tree_in_bounds(&self, row: usize, column: usize) -> bool {
+        let word = self.words[row * self.words_per_row + column / BITS_PER_WORD];
+        word & (1 << (column % BITS_PER_WORD)) != 0
+    }
 }
 
-fn is_rectangular(map: &str) -> bool {
-    map.lines().map(|row| row.len()).all_equal()
+impl FromStr for PackedTreeMap {
+    type Err = Day3Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        validate(s, &Legend::default())?;
+        let width = s.lines().next().map(str::len).unwrap_or(0);
+        let height = s.lines().count();
+        // Validation guarantees that every row has at least one square
+        let words_per_row = (width - 1) / BITS_PER_WORD + 1;
+
+        let mut words = vec![0u64; words_per_row * height];
+        for (r, line) in s.lines().enumerate() {
+            for (c, _) in line.char_indices().filter(|&(_, square)| square == '#') {
+                words[r * words_per_row + c / BITS_PER_WORD] |= 1 << (c % BITS_PER_WORD);
+            }
+        }
+        Ok(PackedTreeMap {
+            width,
+            height,
+            words_per_row,
+            words,
+        })
+    }
+}
+
+fn validate(map: &str, legend: &Legend) -> Result<(), Day3Error> {
+    if map.lines().all(str::is_empty) {
+        return Err(Day3Error::EmptyMap);
+    }
+
+    for (r, line) in map.lines().enumerate() {
+        if let Some((c, character)) = line
+            .chars()
+            .enumerate()
+            .find(|&(_, character)| legend.tile(character).is_none())
+        {
+            return Err(Day3Error::InvalidMap {
+                row: r + 1,
+                column: c + 1,
+                character,
+            });
+        }
+    }
+
+    let width_of = |line: &str| line.chars().count();
+    let expected_width = map.lines().next().map(width_of).unwrap_or(0);
+    if let Some((r, line)) = map
+        .lines()
+        .enumerate()
+        .find(|&(_, line)| width_of(line) != expected_width)
+    {
+        return Err(Day3Error::NonRectangularMap {
+            row: r + 1,
+            width: width_of(line),
+            expected_width,
+        });
+    }
+
+    Ok(())
 }
 
 fn to_2d_array(map: &str) -> Result<Array2<char>, Day3Error> {
     let width = map
         .lines()
-        .map(|row| row.len())
+        .map(|row| row.chars().count())
         .next()
-        .unwrap_or_else(|| todo!());
+        .ok_or(Day3Error::EmptyMap)?;
     let height = map.lines().count();
 
     let mut result = Array2::<char>::default((height, width));
     for (r, line) in map.lines().enumerate() {
-        for (c, character) in line.char_indices() {
+        for (c, character) in line.chars().enumerate() {
             result[[r, c]] = character;
         }
     }
     Ok(result)
 }
 
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub struct Direction {
     pub right: u32,
     pub down: u32,
 }
 
-#[derive(Error, Debug)]
+// Parses a direction written as "RIGHT,DOWN", e.g. "3,1"
+impl FromStr for Direction {
+    type Err = Day3Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (right, down) = parse_steps(s)?;
+        Ok(Direction { right, down })
+    }
+}
+
+// A validated direction that may also go left, where a negative `right` means going left
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Slope {
+    right: i64,
+    down: usize,
+}
+
+impl Slope {
+    pub fn new(right: i64, down: usize) -> Result<Slope, Day3Error> {
+        if down == 0 {
+            return Err(Day3Error::ZeroDown);
+        }
+        Ok(Slope { right, down })
+    }
+
+    pub fn right(&self) -> i64 {
+        self.right
+    }
+
+    pub fn down(&self) -> usize {
+        self.down
+    }
+}
+
+impl TryFrom<Direction> for Slope {
+    type Error = Day3Error;
+
+    fn try_from(direction: Direction) -> Result<Self, Self::Error> {
+        Slope::new(i64::from(direction.right), direction.down as usize)
+    }
+}
+
+// Parses a slope written as "RIGHT,DOWN", e.g. "3,1" or "-2,1"
+impl FromStr for Slope {
+    type Err = Day3Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (right, down) = parse_steps(s)?;
+        Slope::new(right, down)
+    }
+}
+
+// Describes a slope like "Right 3, down 1" or "Left 2, down 1"
+impl fmt::Display for Slope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.right < 0 {
+            write!(f, "Left {}, down {}", -self.right, self.down)
+        } else {
+            write!(f, "Right {}, down {}", self.right, self.down)
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SlopeRanking {
+    FewestTrees,
+    MostTrees,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct RankedSlope {
+    pub slope: Slope,
+    pub trees_encountered: u32,
+}
+
+// The slopes that part 2 of the puzzle checks
+pub const PUZZLE_SLOPES: [Slope; 5] = [
+    Slope { right: 1, down: 1 },
+    Slope { right: 3, down: 1 },
+    Slope { right: 5, down: 1 },
+    Slope { right: 7, down: 1 },
+    Slope { right: 1, down: 2 },
+];
+
+fn parse_steps<R: FromStr, D: FromStr>(s: &str) -> Result<(R, D), Day3Error> {
+    let invalid_direction = || Day3Error::InvalidDirection {
+        direction: s.to_string(),
+    };
+    let mut steps = s.split(',').map(str::trim);
+    match (steps.next(), steps.next(), steps.next()) {
+        (Some(right), Some(down), None) => Ok((
+            right.parse().map_err(|_| invalid_direction())?,
+            down.parse().map_err(|_| invalid_direction())?,
+        )),
+        _ => Err(invalid_direction()),
+    }
+}
+
+// Multiplies together the trees encountered on several slopes, or returns `None` if the product
+// is too large for a `u64`.
+pub fn product_of_trees_encountered(trees_encountered: &[u32]) -> Option<u64> {
+    trees_encountered.iter().try_fold(1u64, |product, &trees| {
+        product.checked_mul(u64::from(trees))
+    })
+}
+
+#[derive(Error, Debug, PartialEq)]
 pub enum Day3Error {
-    #[error("map was expected to contain just '.' and '#' characters, but it also contained other characters")]
-    InvalidMap,
+    #[error("map was expected to contain just the characters in its legend, by default '.' and '#', but row {row} had '{character}' at column {column}")]
+    InvalidMap {
+        row: usize,
+        column: usize,
+        character: char,
+    },
+    #[error("map was expected to be rectangular, but row {row} was {width} characters wide rather than {expected_width}")]
+    NonRectangularMap {
+        row: usize,
+        width: usize,
+        expected_width: usize,
+    },
+    #[error("map was expected to have at least one row, but it was empty")]
+    EmptyMap,
+    #[error("direction was expected to look like \"RIGHT,DOWN\", e.g. \"3,1\", but it was \"{direction}\"")]
+    InvalidDirection { direction: String },
+    #[error("moves were expected to look like \"RIGHT,DOWN\" or \"FIRST..LAST,DOWN\", e.g. \"1..3,1\", but they were \"{moves}\"")]
+    InvalidMoves { moves: String },
+    #[error("tile was expected to look like \"SYMBOL,NAME,WEIGHT\", e.g. \"^,rock,2\", but it was \"{tile}\"")]
+    InvalidTile { tile: String },
+    #[error("direction was expected to go down at least 1 row at a time, but it went down 0, so it would never reach the bottom")]
+    ZeroDown,
 }
 
 #[cfg(test)]
@@ -195,6 +805,827 @@ mod given_large_map_with_trees {
     }
 }
 
+// The example map from the puzzle description
+#[cfg(test)]
+const EXAMPLE_MAP: &str = indoc::indoc! { "
+    ..##.......
+    #...#...#..
+    .#....#..#.
+    ..#.#...#.#
+    .#...##..#.
+    ..#.##.....
+    .#.#.#....#
+    .#........#
+    #.##...#...
+    #...##....#
+    .#..#...#.#"
+};
+
+#[cfg(test)]
+mod given_parsed_tree_map {
+    use super::*;
+    use indoc::indoc;
+
+    mod when_measuring_it {
+        use super::*;
+
+        #[test]
+        fn then_it_has_the_width_and_height_of_the_text() {
+            let tree_map = indoc! { "
+                ..##.......
+                #...#...#..
+                .#....#..#."
+            }
+            .parse::<TreeMap>()
+            .unwrap();
+
+            assert_eq!(tree_map.width(), 11);
+            assert_eq!(tree_map.height(), 3);
+        }
+    }
+
+    mod when_looking_for_trees {
+        use super::*;
+        use test_case::test_case;
+
+        #[test_case(0, 0, false ; "open square")]
+        #[test_case(0, 2, true ; "tree")]
+        #[test_case(1, 11, true ; "tree after wrapping once")]
+        #[test_case(2, 23, true ; "tree after wrapping twice")]
+        #[test_case(2, 22, false ; "open square after wrapping twice")]
+        fn then_columns_wrap_around(row: usize, column: usize, expected_result: bool) {
+            let tree_map = indoc! { "
+                ..##.......
+                #...#...#..
+                .#....#..#."
+            }
+            .parse::<TreeMap>()
+            .unwrap();
+
+            assert_eq!(tree_map.is_tree(row, column), expected_result);
+        }
+    }
+
+    mod when_sliding_down_it_in_many_directions {
+        use super::*;
+
+        #[test]
+        fn then_each_slope_reuses_the_same_parsed_map() {
+            let tree_map = EXAMPLE_MAP.parse::<TreeMap>().unwrap();
+
+            let result = [(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)]
+                .iter()
+                .map(|&(right, down)| tree_map.trees_on_slope(Slope::new(right, down).unwrap()))
+                .collect::<Vec<_>>();
+
+            assert_eq!(result, vec![2, 7, 3, 4, 2]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod given_slopes_going_left {
+    use super::*;
+
+    mod when_sliding_down_map {
+        use super::*;
+        use test_case::test_case;
+
+        // Going left by n on a map 11 squares wide wraps around to the same squares as going right
+        // by 11 - n
+        #[test_case(-1, 10 ; "left 1")]
+        #[test_case(-3, 8 ; "left 3")]
+        #[test_case(-12, 10 ; "left more than the width")]
+        fn then_columns_wrap_around_from_the_left_edge(left: i64, equivalent_right: i64) {
+            let tree_map = EXAMPLE_MAP.parse::<TreeMap>().unwrap();
+
+            let result = tree_map.trees_on_slope(Slope::new(left, 1).unwrap());
+
+            assert_eq!(
+                result,
+                tree_map.trees_on_slope(Slope::new(equivalent_right, 1).unwrap())
+            );
+        }
+
+        #[test]
+        fn then_we_encounter_some_trees() {
+            let tree_map = EXAMPLE_MAP.parse::<TreeMap>().unwrap();
+
+            let result = tree_map.trees_on_slope(Slope::new(-3, 1).unwrap());
+
+            assert_eq!(result, 3);
+        }
+    }
+
+    mod when_describing_them {
+        use super::*;
+
+        #[test]
+        fn then_they_say_left() {
+            assert_eq!(Slope::new(-2, 1).unwrap().to_string(), "Left 2, down 1");
+            assert_eq!(Slope::new(2, 1).unwrap().to_string(), "Right 2, down 1");
+        }
+    }
+}
+
+#[cfg(test)]
+mod given_map_to_find_the_best_slope_on {
+    use super::*;
+    use indoc::indoc;
+
+    fn slopes_and_trees(ranked_slopes: Vec<RankedSlope>) -> Vec<(i64, usize, u32)> {
+        ranked_slopes
+            .into_iter()
+            .map(|ranked| {
+                (
+                    ranked.slope.right(),
+                    ranked.slope.down(),
+                    ranked.trees_encountered,
+                )
+            })
+            .collect()
+    }
+
+    mod when_ranking_slopes_by_fewest_trees {
+        use super::*;
+
+        #[test]
+        fn then_ties_are_broken_by_step_size() {
+            let tree_map = indoc! { "
+                .#.#
+                ..#.
+                #.#.
+                .#.."
+            }
+            .parse::<TreeMap>()
+            .unwrap();
+
+            let result = tree_map.rank_slopes(2, 2, SlopeRanking::FewestTrees);
+
+            assert_eq!(
+                slopes_and_trees(result),
+                vec![
+                    (1, 2, 0),
+                    (0, 1, 1),
+                    (1, 1, 1),
+                    (0, 2, 1),
+                    (2, 2, 1),
+                    (2, 1, 2),
+                ]
+            );
+        }
+    }
+
+    mod when_ranking_slopes_by_most_trees {
+        use super::*;
+
+        #[test]
+        fn then_the_slope_with_most_trees_comes_first() {
+            let tree_map = indoc! { "
+                .#.#
+                ..#.
+                #.#.
+                .#.."
+            }
+            .parse::<TreeMap>()
+            .unwrap();
+
+            let result = tree_map.rank_slopes(2, 2, SlopeRanking::MostTrees);
+
+            assert_eq!(slopes_and_trees(result)[0], (2, 1, 2));
+        }
+    }
+
+    mod when_ranking_no_slopes {
+        use super::*;
+
+        #[test]
+        fn then_there_are_none() {
+            let tree_map = indoc! { "
+                .#.#
+                ..#.
+                #.#.
+                .#.."
+            }
+            .parse::<TreeMap>()
+            .unwrap();
+
+            let result = tree_map.rank_slopes(3, 0, SlopeRanking::FewestTrees);
+
+            assert!(result.is_empty());
+        }
+    }
+}
+
+#[cfg(test)]
+mod given_map_to_render {
+    use super::*;
+    use indoc::indoc;
+
+    mod when_rendering_the_path_down_a_slope {
+        use super::*;
+
+        #[test]
+        fn then_the_map_is_tiled_as_far_right_as_the_path_goes() {
+            let tree_map = indoc! { "
+                ..#
+                #..
+                .#."
+            }
+            .parse::<TreeMap>()
+            .unwrap();
+
+            let result = tree_map.render_slope(Slope::new(2, 1).unwrap(), false);
+
+            assert_eq!(
+                result,
+                indoc! { "
+                    O.#..#
+                    #.O#..
+                    .#..X.
+                " }
+            );
+        }
+
+        #[test]
+        fn then_the_map_is_tiled_as_far_left_as_the_path_goes() {
+            let tree_map = indoc! { "
+                ..#
+                #..
+                .#."
+            }
+            .parse::<TreeMap>()
+            .unwrap();
+
+            let result = tree_map.render_slope(Slope::new(-1, 1).unwrap(), false);
+
+            assert_eq!(
+                result,
+                indoc! { "
+                    ..#O.#
+                    #.O#..
+                    .X..#.
+                " }
+            );
+        }
+
+        #[test]
+        fn then_visited_squares_may_be_coloured() {
+            let tree_map = indoc! { "
+                .#
+                #."
+            }
+            .parse::<TreeMap>()
+            .unwrap();
+
+            let result = tree_map.render_slope(Slope::new(0, 1).unwrap(), true);
+
+            assert_eq!(
+                result,
+                format!("{}O{}#\n{}X{}.\n", GREEN, RESET, RED, RESET)
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod given_map_with_a_legend {
+    use super::*;
+    use indoc::indoc;
+
+    const MAP: &str = indoc! { "
+        ..#^
+        #^.~
+        .~#.
+        ~..#"
+    };
+
+    fn legend() -> Legend {
+        Legend::default()
+            .with_tile("^,rock,3".parse().unwrap())
+            .with_tile("~,ice,1".parse().unwrap())
+    }
+
+    fn counts(tiles_encountered: &TilesEncountered) -> Vec<(&str, u32)> {
+        tiles_encountered
+            .counts
+            .iter()
+            .map(|tile_count| (tile_count.tile.name.as_str(), tile_count.count))
+            .collect()
+    }
+
+    mod when_sliding_down_map {
+        use super::*;
+        use test_case::test_case;
+
+        #[test_case(1, 1, &[("open", 1), ("tree", 2), ("rock", 1), ("ice", 0)], 5 ; "right 1 down 1")]
+        #[test_case(3, 1, &[("open", 2), ("tree", 1), ("rock", 0), ("ice", 1)], 2 ; "right 3 down 1")]
+        fn then_each_tile_is_counted_and_weighted(
+            right: i64,
+            down: usize,
+            expected_counts: &[(&str, u32)],
+            expected_weighted_total: u32,
+        ) {
+            let tree_map = TreeMap::with_legend(MAP, legend()).unwrap();
+
+            let result = tree_map.tiles_on_slope(Slope::new(right, down).unwrap());
+
+            assert_eq!(counts(&result), expected_counts);
+            assert_eq!(result.weighted_total, expected_weighted_total);
+        }
+
+        #[test]
+        fn then_each_collision_counts_as_its_weight_in_trees() {
+            let tree_map = TreeMap::with_legend(MAP, legend()).unwrap();
+
+            let result = tree_map.trees_on_slope(Slope::new(1, 1).unwrap());
+
+            assert_eq!(result, 5);
+        }
+
+        #[test]
+        fn then_trees_redefined_as_open_squares_are_not_trees() {
+            let legend = Legend::default().with_tile("#,open,0".parse().unwrap());
+            let tree_map = TreeMap::with_legend("#\n#", legend).unwrap();
+
+            let result = tree_map.trees_on_slope(Slope::new(0, 1).unwrap());
+
+            assert_eq!(result, 0);
+            assert!(!tree_map.is_tree(0, 0));
+        }
+
+        #[test]
+        fn then_tiles_may_be_any_character() {
+            let legend = Legend::default().with_tile("❄,snow,5".parse().unwrap());
+            let tree_map = TreeMap::with_legend(
+                indoc! { "
+                    ❄..
+                    .#.
+                    ..❄"
+                },
+                legend,
+            )
+            .unwrap();
+
+            let result = tree_map.tiles_on_slope(Slope::new(1, 1).unwrap());
+
+            assert_eq!(tree_map.width(), 3);
+            assert_eq!(counts(&result), [("open", 0), ("tree", 1), ("snow", 2)]);
+            assert_eq!(result.weighted_total, 11);
+        }
+
+        #[test]
+        fn then_rows_are_measured_in_characters() {
+            let legend = Legend::default().with_tile("❄,snow,5".parse().unwrap());
+
+            let result = TreeMap::with_legend("❄.\n....", legend);
+
+            assert_eq!(
+                result.unwrap_err(),
+                Day3Error::NonRectangularMap {
+                    row: 2,
+                    width: 4,
+                    expected_width: 2
+                }
+            );
+        }
+
+        #[test]
+        fn then_tiles_that_replace_default_ones_take_their_place() {
+            let legend = legend().with_tile("#,tree,10".parse().unwrap());
+            let tree_map = TreeMap::with_legend(MAP, legend).unwrap();
+
+            let result = tree_map.tiles_on_slope(Slope::new(1, 1).unwrap());
+
+            assert_eq!(
+                counts(&result),
+                [("open", 1), ("tree", 2), ("rock", 1), ("ice", 0)]
+            );
+            assert_eq!(result.weighted_total, 23);
+        }
+
+        #[test]
+        fn then_characters_not_in_the_legend_are_still_invalid() {
+            let result = TreeMap::with_legend(
+                MAP,
+                Legend::default().with_tile("^,rock,3".parse().unwrap()),
+            );
+
+            assert_eq!(
+                result.unwrap_err(),
+                Day3Error::InvalidMap {
+                    row: 2,
+                    column: 4,
+                    character: '~'
+                }
+            );
+        }
+    }
+
+    mod when_ranking_slopes {
+        use super::*;
+
+        #[test]
+        fn then_collisions_are_ranked_by_weight() {
+            let tree_map = TreeMap::with_legend("^.\n^.", legend()).unwrap();
+
+            let result = tree_map.rank_slopes(1, 1, SlopeRanking::FewestTrees);
+
+            assert_eq!(
+                result
+                    .iter()
+                    .map(|ranked| (ranked.slope, ranked.trees_encountered))
+                    .collect::<Vec<_>>(),
+                [
+                    (Slope::new(1, 1).unwrap(), 3),
+                    (Slope::new(0, 1).unwrap(), 6)
+                ]
+            );
+        }
+    }
+
+    mod when_finding_a_route_down {
+        use super::*;
+
+        #[test]
+        fn then_collisions_cost_their_weight() {
+            let tree_map = TreeMap::with_legend("^^\n^^", legend()).unwrap();
+
+            let result = tree_map.fewest_trees_route(&[Slope::new(0, 1).unwrap()]);
+
+            assert_eq!(result.unwrap().trees_encountered, 6);
+        }
+
+        #[test]
+        fn then_heavier_collisions_are_avoided() {
+            let tree_map = TreeMap::with_legend("..\n^~", legend()).unwrap();
+
+            let result = tree_map.fewest_trees_route(&parse_moves("0..1,1").unwrap());
+
+            let route = result.unwrap();
+            assert_eq!(route.squares, [(0, 0), (1, 1)]);
+            assert_eq!(route.trees_encountered, 1);
+        }
+    }
+
+    mod when_rendering_the_path_down_a_slope {
+        use super::*;
+
+        #[test]
+        fn then_visited_tiles_with_a_collision_weight_are_marked_as_collisions() {
+            let tree_map = TreeMap::with_legend(MAP, legend()).unwrap();
+
+            let result = tree_map.render_slope(Slope::new(3, 1).unwrap(), false);
+
+            assert_eq!(
+                result,
+                indoc! { "
+                    O.#^..#^..#^
+                    #^.X#^.~#^.~
+                    .~#..~X..~#.
+                    ~..#~..#~O.#
+                " }
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod given_legend_tiles_as_text {
+    use super::*;
+
+    mod when_parsing_them {
+        use super::*;
+        use test_case::test_case;
+
+        #[test_case("^,rock,2" => Ok(Tile { symbol: '^', name: "rock".to_string(), collision_weight: 2 }) ; "tile")]
+        #[test_case("~,thin ice,0" => Ok(Tile { symbol: '~', name: "thin ice".to_string(), collision_weight: 0 }) ; "tile with spaced name")]
+        #[test_case("^^,rock,2" => Err(Day3Error::InvalidTile { tile: "^^,rock,2".to_string() }) ; "tile with long symbol")]
+        #[test_case(",rock,2" => Err(Day3Error::InvalidTile { tile: ",rock,2".to_string() }) ; "tile without symbol")]
+        #[test_case("^,,2" => Err(Day3Error::InvalidTile { tile: "^,,2".to_string() }) ; "tile without name")]
+        #[test_case("^,rock,-2" => Err(Day3Error::InvalidTile { tile: "^,rock,-2".to_string() }) ; "tile with negative weight")]
+        #[test_case("^,rock" => Err(Day3Error::InvalidTile { tile: "^,rock".to_string() }) ; "tile without weight")]
+        fn then_they_are_parsed_if_well_formed(tile: &str) -> Result<Tile, Day3Error> {
+            tile.parse()
+        }
+    }
+}
+
+#[cfg(test)]
+mod given_packed_tree_map {
+    use super::*;
+
+    mod when_looking_for_trees {
+        use super::*;
+        use test_case::test_case;
+
+        #[test_case(0, 0, false ; "open square")]
+        #[test_case(0, 64, true ; "tree in the second word of a row")]
+        #[test_case(1, 69, true ; "tree at the end of a row")]
+        #[test_case(1, 70, true ; "tree after wrapping once")]
+        #[test_case(0, 134, true ; "tree in the second word after wrapping once")]
+        #[test_case(1, 71, false ; "open square after wrapping once")]
+        fn then_columns_wrap_around(row: usize, column: usize, expected_result: bool) {
+            let row_0 = format!("{}#{}", ".".repeat(64), ".".repeat(5));
+            let row_1 = format!("#{}#", ".".repeat(68));
+            let tree_map = format!("{}\n{}", row_0, row_1)
+                .parse::<PackedTreeMap>()
+                .unwrap();
+
+            assert_eq!(tree_map.width(), 70);
+            assert_eq!(tree_map.height(), 2);
+            assert_eq!(tree_map.is_tree(row, column), expected_result);
+        }
+    }
+
+    mod when_sliding_down_it {
+        use super::*;
+
+        #[test]
+        fn then_we_encounter_the_same_trees_as_an_unpacked_map(
+        ) -> Result<(), Box<dyn std::error::Error>> {
+            let puzzle_input = std::fs::read_to_string("tests/input.txt")?;
+            for map in &[EXAMPLE_MAP, puzzle_input.trim()] {
+                let tree_map = map.parse::<TreeMap>()?;
+                let packed_tree_map = map.parse::<PackedTreeMap>()?;
+
+                for &right in &[-3, 0, 1, 3, 5, 7, 100] {
+                    for &down in &[1, 2] {
+                        let slope = Slope::new(right, down)?;
+                        assert_eq!(
+                            packed_tree_map.trees_on_slope(slope),
+                            tree_map.trees_on_slope(slope)
+                        );
+                    }
+                }
+            }
+            Ok(())
+        }
+    }
+
+    mod when_measuring_its_memory {
+        use super::*;
+
+        #[test]
+        fn then_it_takes_up_less_than_an_unpacked_map() -> Result<(), Box<dyn std::error::Error>> {
+            let map = vec![".#.".repeat(100); 100].join("\n");
+            let tree_map = map.parse::<TreeMap>()?;
+            let packed_tree_map = map.parse::<PackedTreeMap>()?;
+
+            assert_eq!(tree_map.size_in_bytes(), 300 * 100 * 4);
+            assert_eq!(packed_tree_map.size_in_bytes(), 5 * 100 * 8);
+            Ok(())
+        }
+    }
+
+    mod when_parsing_an_invalid_map {
+        use super::*;
+
+        #[test]
+        fn then_we_are_told_what_is_wrong() {
+            assert_eq!("".parse::<PackedTreeMap>(), Err(Day3Error::EmptyMap));
+            assert_eq!(
+                "..\n.x".parse::<PackedTreeMap>(),
+                Err(Day3Error::InvalidMap {
+                    row: 2,
+                    column: 2,
+                    character: 'x'
+                })
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod given_map_to_find_a_route_down {
+    use super::*;
+    use indoc::indoc;
+
+    fn moves(right: std::ops::RangeInclusive<i64>, down: usize) -> Vec<Slope> {
+        right
+            .map(|right| Slope::new(right, down).unwrap())
+            .collect()
+    }
+
+    mod when_finding_the_route_with_fewest_trees {
+        use super::*;
+
+        #[test]
+        fn then_it_dodges_the_trees() -> Result<(), Box<dyn std::error::Error>> {
+            let tree_map = indoc! { "
+                ...
+                .#.
+                #.."
+            }
+            .parse::<TreeMap>()?;
+
+            let result = tree_map.fewest_trees_route(&moves(0..=1, 1));
+
+            assert_eq!(
+                result,
+                Some(Route {
+                    squares: vec![(0, 0), (1, 0), (2, 1)],
+                    moves: vec![Slope::new(0, 1)?, Slope::new(1, 1)?],
+                    trees_encountered: 0,
+                })
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn then_it_wraps_around_both_edges() -> Result<(), Box<dyn std::error::Error>> {
+            let tree_map = indoc! { "
+                .##
+                ##.
+                #.#"
+            }
+            .parse::<TreeMap>()?;
+
+            let result = tree_map.fewest_trees_route(&moves(-1..=-1, 1));
+
+            assert_eq!(
+                result,
+                Some(Route {
+                    squares: vec![(0, 0), (1, 2), (2, 1)],
+                    moves: vec![Slope::new(-1, 1)?; 2],
+                    trees_encountered: 0,
+                })
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn then_it_encounters_no_more_trees_than_any_single_slope(
+        ) -> Result<(), Box<dyn std::error::Error>> {
+            let tree_map = std::fs::read_to_string("tests/input.txt")?.parse::<TreeMap>()?;
+            let allowed_moves = moves(1..=3, 1);
+
+            let result = tree_map.fewest_trees_route(&allowed_moves).unwrap();
+
+            for &slope in &allowed_moves {
+                assert!(result.trees_encountered <= tree_map.trees_on_slope(slope));
+            }
+            assert_eq!(result.squares.len(), tree_map.height());
+            assert_eq!(result.moves.len(), result.squares.len() - 1);
+            let trees_on_route = result
+                .squares
+                .iter()
+                .filter(|&&(row, column)| tree_map.is_tree(row, column))
+                .count();
+            assert_eq!(trees_on_route as u32, result.trees_encountered);
+            Ok(())
+        }
+
+        #[test]
+        fn then_a_single_move_follows_its_slope() -> Result<(), Box<dyn std::error::Error>> {
+            let tree_map = std::fs::read_to_string("tests/input.txt")?.parse::<TreeMap>()?;
+            let slope = Slope::new(1, 2)?;
+
+            let result = tree_map.fewest_trees_route(&[slope]).unwrap();
+
+            assert_eq!(result.trees_encountered, tree_map.trees_on_slope(slope));
+            Ok(())
+        }
+
+        #[test]
+        fn then_there_is_none_without_any_moves() -> Result<(), Box<dyn std::error::Error>> {
+            let tree_map = "..#".parse::<TreeMap>()?;
+
+            assert_eq!(tree_map.fewest_trees_route(&[]), None);
+            Ok(())
+        }
+    }
+
+    mod when_parsing_moves {
+        use super::*;
+        use test_case::test_case;
+
+        #[test_case("1..3,1", moves(1..=3, 1) ; "range of moves")]
+        #[test_case("-2..-1, 2", moves(-2..=-1, 2) ; "range of moves going left")]
+        #[test_case("3,1", moves(3..=3, 1) ; "single move")]
+        fn then_every_move_is_listed(text: &str, expected_moves: Vec<Slope>) {
+            assert_eq!(parse_moves(text), Ok(expected_moves));
+        }
+
+        #[test_case("3..1,1" ; "reversed range")]
+        #[test_case("1..3" ; "missing down")]
+        #[test_case("a..b,1" ; "not numbers")]
+        fn then_invalid_ones_are_rejected(text: &str) {
+            assert_eq!(
+                parse_moves(text),
+                Err(Day3Error::InvalidMoves {
+                    moves: text.to_string()
+                })
+            );
+        }
+
+        #[test]
+        fn then_moves_not_going_down_are_rejected() {
+            assert_eq!(parse_moves("1..3,0"), Err(Day3Error::ZeroDown));
+        }
+    }
+}
+
+#[cfg(test)]
+mod given_slopes_not_going_down {
+    use super::*;
+
+    #[test]
+    fn then_they_are_rejected() {
+        assert_eq!(Slope::new(3, 0), Err(Day3Error::ZeroDown));
+        assert_eq!("-3,0".parse::<Slope>(), Err(Day3Error::ZeroDown));
+        assert_eq!(
+            trees_encountered_in_direction(".#\n#.", Direction { right: 1, down: 0 }),
+            Err(Day3Error::ZeroDown)
+        );
+    }
+}
+
+#[cfg(test)]
+mod given_directions_as_text {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case("3,1", Direction { right: 3, down: 1 } ; "right 3, down 1")]
+    #[test_case(" 1 , 2 ", Direction { right: 1, down: 2 } ; "with spaces")]
+    fn then_they_parse_into_directions(text: &str, expected_direction: Direction) {
+        assert_eq!(text.parse::<Direction>(), Ok(expected_direction));
+    }
+
+    #[test_case("3" ; "missing down")]
+    #[test_case("3,1,2" ; "too many steps")]
+    #[test_case("-3,1" ; "negative step")]
+    #[test_case("right,down" ; "not numbers")]
+    fn then_invalid_ones_are_rejected(text: &str) {
+        assert_eq!(
+            text.parse::<Direction>(),
+            Err(Day3Error::InvalidDirection {
+                direction: text.to_string()
+            })
+        );
+    }
+}
+
+#[cfg(test)]
+mod given_trees_encountered_on_many_slopes {
+    use super::*;
+
+    #[test]
+    fn then_their_product_does_not_overflow_u32() {
+        let trees_encountered = [u32::MAX, 2];
+
+        let result = product_of_trees_encountered(&trees_encountered);
+
+        assert_eq!(result, Some(u64::from(u32::MAX) * 2));
+    }
+
+    #[test]
+    fn then_a_product_too_large_for_u64_is_rejected() {
+        let trees_encountered = [u32::MAX, u32::MAX, u32::MAX];
+
+        let result = product_of_trees_encountered(&trees_encountered);
+
+        assert_eq!(result, None);
+    }
+}
+
+#[cfg(test)]
+mod given_invalid_maps {
+    use super::*;
+    use indoc::indoc;
+
+    mod when_sliding_down_map {
+        use super::*;
+        use test_case::test_case;
+
+        #[test_case("", Day3Error::EmptyMap ; "empty map")]
+        #[test_case("\n\n", Day3Error::EmptyMap ; "map of empty rows")]
+        #[test_case(
+            indoc! { "
+                ..#
+                .o#
+                ..x"
+            },
+            Day3Error::InvalidMap { row: 2, column: 2, character: 'o' }
+            ; "map with other characters"
+        )]
+        #[test_case(
+            indoc! { "
+                ..#
+                .#
+                ..#."
+            },
+            Day3Error::NonRectangularMap { row: 2, width: 2, expected_width: 3 }
+            ; "map with mismatched rows"
+        )]
+        fn then_we_are_told_what_is_wrong(map: &str, expected_error: Day3Error) {
+            let result = trees_encountered(map);
+
+            assert_eq!(result.unwrap_err(), expected_error);
+        }
+    }
+}
+
 #[cfg(test)]
 mod given_puzzle_input_map_from_advent_of_code_2020 {
     use super::*;
@@ -212,5 +1643,19 @@ mod given_puzzle_input_map_from_advent_of_code_2020 {
 
             Ok(())
         }
+
+        #[test]
+        fn then_the_default_legend_weighs_just_the_trees() -> Result<(), Box<dyn std::error::Error>>
+        {
+            let tree_map = std::fs::read_to_string("tests/input.txt")?.parse::<TreeMap>()?;
+
+            for &slope in PUZZLE_SLOPES.iter() {
+                let result = tree_map.tiles_on_slope(slope);
+
+                assert_eq!(result.weighted_total, tree_map.trees_on_slope(slope));
+            }
+
+            Ok(())
+        }
     }
 }
@@ -1,6 +1,10 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use atty::Stream;
 use clap::{clap_app, crate_authors, crate_description, crate_name, crate_version};
-use day_3::{trees_encountered, trees_encountered_in_direction, Direction};
+use day_3::{
+    parse_moves, product_of_trees_encountered, Legend, Slope, SlopeRanking, Tile, TilesEncountered,
+    TreeMap, PUZZLE_SLOPES,
+};
 use std::fs::read_to_string;
 use std::path::PathBuf;
 
@@ -14,6 +18,38 @@ fn main() -> Result<()> {
         (@arg ALL_DIRECTIONS:
             --("all-directions")
             "Should we go in all directions, not just right 3 and down 1?")
+        (@arg SLOPE: --slope +takes_value +multiple number_of_values(1) +allow_hyphen_values
+            "A direction to go in, as RIGHT,DOWN, where a negative RIGHT goes left. May be given \
+             more than once, in which case it replaces the directions that --all-directions goes \
+             in.")
+        (@arg RANK_SLOPES: --("rank-slopes") conflicts_with[ALL_DIRECTIONS SLOPE]
+            "Should we try every slope up to --max-right and --max-down, and list them from fewest \
+             trees encountered to most?")
+        (@arg MAX_RIGHT: --("max-right") +takes_value requires[RANK_SLOPES]
+            "How far right the slopes being ranked may go [default: 7]")
+        (@arg MAX_DOWN: --("max-down") +takes_value requires[RANK_SLOPES]
+            "How far down the slopes being ranked may go [default: 2]")
+        (@arg MOST_TREES: --("most-trees") requires[RANK_SLOPES]
+            "Should we rank slopes from most trees encountered to fewest instead?")
+        (@arg RENDER: --render conflicts_with[ALL_DIRECTIONS RANK_SLOPES]
+            "Should we draw the path down each --slope, or right 3 and down 1 if none are given, \
+             marking open squares with O and trees with X?")
+        (@arg COLOUR: --colour +takes_value possible_value[auto always never] requires[RENDER]
+            "When to colour the path being drawn [default: auto, which colours it only if the \
+             output is a terminal]")
+        (@arg FEWEST_TREES_ROUTE: --("fewest-trees-route")
+            conflicts_with[ALL_DIRECTIONS SLOPE RANK_SLOPES RENDER]
+            "Should we find the route down that encounters the fewest trees, using any of --moves \
+             at each step?")
+        (@arg MOVES: --moves +takes_value +multiple number_of_values(1) +allow_hyphen_values
+            requires[FEWEST_TREES_ROUTE]
+            "The moves allowed at each step of the route, as RIGHT,DOWN or FIRST..LAST,DOWN for \
+             every move right from FIRST to LAST. May be given more than once. [default: 1..3,1]")
+        (@arg TILE: --tile +takes_value +multiple number_of_values(1)
+            "A tile the map may have besides open squares ('.') and trees ('#'), as \
+             SYMBOL,NAME,WEIGHT, where WEIGHT is what running into it costs. May be given more than \
+             once, and may replace '.' or '#'. Every tile then counts as WEIGHT trees, and each \
+             slope also reports how many of each tile it encounters.")
     )
     .get_matches();
 
@@ -21,27 +57,127 @@ fn main() -> Result<()> {
     let map = read_to_string(map_file_path)?;
     let sanitized_map = map.trim();
 
-    if matches.is_present("ALL_DIRECTIONS") {
-        let right_1_down_1 =
-            trees_encountered_in_direction(&sanitized_map, Direction { right: 1, down: 1 })?;
-        println!("Right 1, down 1: {}", right_1_down_1);
-        let right_3_down_1 =
-            trees_encountered_in_direction(&sanitized_map, Direction { right: 3, down: 1 })?;
-        println!("Right 3, down 1: {}", right_3_down_1);
-        let right_5_down_1 =
-            trees_encountered_in_direction(&sanitized_map, Direction { right: 5, down: 1 })?;
-        println!("Right 5, down 1: {}", right_5_down_1);
-        let right_7_down_1 =
-            trees_encountered_in_direction(&sanitized_map, Direction { right: 7, down: 1 })?;
-        println!("Right 7, down 1: {}", right_7_down_1);
-        let right_1_down_2 =
-            trees_encountered_in_direction(&sanitized_map, Direction { right: 1, down: 2 })?;
-        println!("Right 1, down 2: {}", right_1_down_2);
-        let product =
-            right_1_down_1 * right_3_down_1 * right_5_down_1 * right_7_down_1 * right_1_down_2;
+    let legend = matches
+        .values_of("TILE")
+        .unwrap_or_default()
+        .map(str::parse::<Tile>)
+        .try_fold(Legend::default(), |legend, tile| {
+            tile.map(|tile| legend.with_tile(tile))
+        })?;
+    let tree_map = || TreeMap::with_legend(sanitized_map, legend.clone());
+    let describe_tiles = |tiles_encountered: &TilesEncountered| {
+        if matches.is_present("TILE") {
+            let counts = tiles_encountered
+                .counts
+                .iter()
+                .map(|tile_count| format!("{} {}", tile_count.tile.name, tile_count.count))
+                .collect::<Vec<_>>();
+            format!(
+                "{} ({})",
+                tiles_encountered.weighted_total,
+                counts.join(", ")
+            )
+        } else {
+            tiles_encountered.weighted_total.to_string()
+        }
+    };
+
+    let slopes = match matches.values_of("SLOPE") {
+        Some(slopes) => slopes
+            .map(str::parse::<Slope>)
+            .collect::<Result<Vec<_>, _>>()?,
+        None if matches.is_present("ALL_DIRECTIONS") => PUZZLE_SLOPES.to_vec(),
+        None => Vec::new(),
+    };
+
+    if matches.is_present("FEWEST_TREES_ROUTE") {
+        let mut moves = Vec::new();
+        for moves_text in matches.values_of("MOVES").unwrap_or_default() {
+            moves.extend(parse_moves(moves_text)?);
+        }
+        if moves.is_empty() {
+            moves = parse_moves("1..3,1")?;
+        }
+        let tree_map = tree_map()?;
+        let route = tree_map
+            .fewest_trees_route(&moves)
+            .ok_or_else(|| anyhow!("There are no moves to find a route down with."))?;
+        let describe_square = |(row, column): (usize, usize)| {
+            format!(
+                "row {}, column {}{}",
+                row + 1,
+                column + 1,
+                match tree_map.tile(row, column) {
+                    Some(tile) if tile.collision_weight > 0 => format!(" ({})", tile.name),
+                    _ => String::new(),
+                }
+            )
+        };
+        println!("Start at {}", describe_square(route.squares[0]));
+        for (&next_move, &square) in route.moves.iter().zip(&route.squares[1..]) {
+            println!("{} to {}", next_move, describe_square(square));
+        }
+        println!("Trees encountered: {}", route.trees_encountered);
+    } else if matches.is_present("RENDER") {
+        let use_colour = match matches.value_of("COLOUR").unwrap_or("auto") {
+            "always" => true,
+            "never" => false,
+            _ => atty::is(Stream::Stdout),
+        };
+        let slopes = if slopes.is_empty() {
+            vec![Slope::new(3, 1)?]
+        } else {
+            slopes
+        };
+        let tree_map = tree_map()?;
+        for (i, slope) in slopes.into_iter().enumerate() {
+            if i > 0 {
+                println!();
+            }
+            println!(
+                "{}: {}",
+                slope,
+                describe_tiles(&tree_map.tiles_on_slope(slope))
+            );
+            print!("{}", tree_map.render_slope(slope, use_colour));
+        }
+    } else if matches.is_present("RANK_SLOPES") {
+        let max_steps = |name: &str, default: &str, direction: &str| -> Result<u32> {
+            let max_steps = matches.value_of(name).unwrap_or(default);
+            max_steps.parse::<u32>().map_err(|_| {
+                anyhow!(
+                    "The value for how far {} the slopes may go is not a whole non-negative number.",
+                    direction
+                )
+            })
+        };
+        let ranking = if matches.is_present("MOST_TREES") {
+            SlopeRanking::MostTrees
+        } else {
+            SlopeRanking::FewestTrees
+        };
+        let tree_map = tree_map()?;
+        for ranked_slope in tree_map.rank_slopes(
+            max_steps("MAX_RIGHT", "7", "right")?,
+            max_steps("MAX_DOWN", "2", "down")?,
+            ranking,
+        ) {
+            println!("{}: {}", ranked_slope.slope, ranked_slope.trees_encountered);
+        }
+    } else if !slopes.is_empty() {
+        let tree_map = tree_map()?;
+        let mut weighted_totals = Vec::new();
+        for slope in slopes {
+            let tiles_encountered = tree_map.tiles_on_slope(slope);
+            println!("{}: {}", slope, describe_tiles(&tiles_encountered));
+            weighted_totals.push(tiles_encountered.weighted_total);
+        }
+        let product = product_of_trees_encountered(&weighted_totals)
+            .ok_or_else(|| anyhow!("The product of the trees encountered is too large."))?;
         println!("Product: {}", product);
     } else {
-        println!("{}", trees_encountered(&sanitized_map)?);
+        let tiles_encountered = tree_map()?.tiles_on_slope(Slope::new(3, 1)?);
+        println!("{}", describe_tiles(&tiles_encountered));
     }
 
     Ok(())
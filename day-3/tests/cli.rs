@@ -104,3 +104,392 @@ mod given_map_with_lots_of_trees {
         }
     }
 }
+
+#[cfg(test)]
+mod given_map_and_slopes {
+    use super::*;
+
+    mod when_sliding_down_map_on_each_slope {
+        use super::*;
+
+        #[test]
+        fn then_we_encounter_some_trees_on_each() -> Result<(), Box<dyn std::error::Error>> {
+            let mut actual_map_file = NamedTempFile::new()?;
+            writeln!(
+                actual_map_file,
+                indoc! { "
+                    ..##.......
+                    #...#...#..
+                    .#....#..#.
+                    ..#.#...#.#
+                    .#...##..#.
+                    ..#.##.....
+                    .#.#.#....#
+                    .#........#
+                    #.##...#...
+                    #...##....#
+                    .#..#...#.#
+                " }
+            )?;
+            let expected_result = indoc! { "
+                Right 3, down 1: 7
+                Right 2, down 3: 1
+                Product: 7
+            " };
+
+            let mut command = Command::cargo_bin("day-3")?;
+            command
+                .arg(actual_map_file.path())
+                .arg("--slope")
+                .arg("3,1")
+                .arg("--slope")
+                .arg("2,3");
+
+            command
+                .assert()
+                .success()
+                .stdout(predicate::eq(expected_result));
+            Ok(())
+        }
+    }
+
+    mod when_sliding_down_map_to_the_left {
+        use super::*;
+
+        #[test]
+        fn then_we_encounter_some_trees() -> Result<(), Box<dyn std::error::Error>> {
+            let mut actual_map_file = NamedTempFile::new()?;
+            writeln!(
+                actual_map_file,
+                indoc! { "
+                    ..##.......
+                    #...#...#..
+                    .#....#..#.
+                    ..#.#...#.#
+                    .#...##..#.
+                    ..#.##.....
+                    .#.#.#....#
+                    .#........#
+                    #.##...#...
+                    #...##....#
+                    .#..#...#.#
+                " }
+            )?;
+            let expected_result = indoc! { "
+                Left 3, down 1: 3
+                Product: 3
+            " };
+
+            let mut command = Command::cargo_bin("day-3")?;
+            command
+                .arg(actual_map_file.path())
+                .arg("--slope")
+                .arg("-3,1");
+
+            command
+                .assert()
+                .success()
+                .stdout(predicate::eq(expected_result));
+            Ok(())
+        }
+    }
+
+    mod when_a_slope_does_not_go_down {
+        use super::*;
+
+        #[test]
+        fn then_we_are_told_it_never_reaches_the_bottom() -> Result<(), Box<dyn std::error::Error>>
+        {
+            let mut actual_map_file = NamedTempFile::new()?;
+            writeln!(actual_map_file, "..#")?;
+
+            let mut command = Command::cargo_bin("day-3")?;
+            command
+                .arg(actual_map_file.path())
+                .arg("--slope")
+                .arg("3,0");
+
+            command
+                .assert()
+                .failure()
+                .stderr(predicate::str::contains("never reach the bottom"));
+            Ok(())
+        }
+    }
+
+    mod when_ranking_slopes {
+        use super::*;
+
+        #[test]
+        fn then_they_are_listed_from_fewest_trees_to_most() -> Result<(), Box<dyn std::error::Error>>
+        {
+            let mut actual_map_file = NamedTempFile::new()?;
+            writeln!(
+                actual_map_file,
+                indoc! { "
+                    .#.#
+                    ..#.
+                    #.#.
+                    .#..
+                " }
+            )?;
+            let expected_result = indoc! { "
+                Right 1, down 2: 0
+                Right 0, down 1: 1
+                Right 1, down 1: 1
+                Right 0, down 2: 1
+                Right 2, down 2: 1
+                Right 2, down 1: 2
+            " };
+
+            let mut command = Command::cargo_bin("day-3")?;
+            command
+                .arg(actual_map_file.path())
+                .arg("--rank-slopes")
+                .arg("--max-right")
+                .arg("2");
+
+            command
+                .assert()
+                .success()
+                .stdout(predicate::eq(expected_result));
+            Ok(())
+        }
+    }
+
+    mod when_rendering_the_path_down_a_slope {
+        use super::*;
+
+        #[test]
+        fn then_the_visited_squares_are_marked() -> Result<(), Box<dyn std::error::Error>> {
+            let mut actual_map_file = NamedTempFile::new()?;
+            writeln!(
+                actual_map_file,
+                indoc! { "
+                    ..#
+                    #..
+                    .#.
+                " }
+            )?;
+            let expected_result = indoc! { "
+                Right 2, down 1: 1
+                O.#..#
+                #.O#..
+                .#..X.
+            " };
+
+            let mut command = Command::cargo_bin("day-3")?;
+            command
+                .arg(actual_map_file.path())
+                .arg("--render")
+                .arg("--slope")
+                .arg("2,1");
+
+            command
+                .assert()
+                .success()
+                .stdout(predicate::eq(expected_result));
+            Ok(())
+        }
+
+        #[test]
+        fn then_the_visited_squares_are_coloured_when_asked(
+        ) -> Result<(), Box<dyn std::error::Error>> {
+            let mut actual_map_file = NamedTempFile::new()?;
+            writeln!(actual_map_file, "#")?;
+            let expected_result = "Right 3, down 1: 1\n\x1b[31mX\x1b[0m\n";
+
+            let mut command = Command::cargo_bin("day-3")?;
+            command
+                .arg(actual_map_file.path())
+                .arg("--render")
+                .arg("--colour")
+                .arg("always");
+
+            command
+                .assert()
+                .success()
+                .stdout(predicate::eq(expected_result));
+            Ok(())
+        }
+    }
+
+    mod when_finding_the_route_with_fewest_trees {
+        use super::*;
+
+        #[test]
+        fn then_each_step_is_listed() -> Result<(), Box<dyn std::error::Error>> {
+            let mut actual_map_file = NamedTempFile::new()?;
+            writeln!(
+                actual_map_file,
+                indoc! { "
+                    ...
+                    .#.
+                    #.#
+                    ##.
+                " }
+            )?;
+            let expected_result = indoc! { "
+                Start at row 1, column 1
+                Right 0, down 1 to row 2, column 1
+                Right 1, down 1 to row 3, column 2
+                Right 1, down 1 to row 4, column 3
+                Trees encountered: 0
+            " };
+
+            let mut command = Command::cargo_bin("day-3")?;
+            command
+                .arg(actual_map_file.path())
+                .arg("--fewest-trees-route")
+                .arg("--moves")
+                .arg("0..1,1");
+
+            command
+                .assert()
+                .success()
+                .stdout(predicate::eq(expected_result));
+            Ok(())
+        }
+    }
+
+    mod when_a_slope_is_malformed {
+        use super::*;
+
+        #[test]
+        fn then_we_are_told_which_one() -> Result<(), Box<dyn std::error::Error>> {
+            let mut actual_map_file = NamedTempFile::new()?;
+            writeln!(actual_map_file, "..#")?;
+
+            let mut command = Command::cargo_bin("day-3")?;
+            command
+                .arg(actual_map_file.path())
+                .arg("--slope")
+                .arg("3;1");
+
+            command
+                .assert()
+                .failure()
+                .stderr(predicate::str::contains(r#"but it was "3;1""#));
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod given_map_with_a_legend {
+    use super::*;
+
+    mod when_sliding_down_map_on_each_slope {
+        use super::*;
+
+        #[test]
+        fn then_we_count_each_tile_and_weigh_the_collisions(
+        ) -> Result<(), Box<dyn std::error::Error>> {
+            let mut actual_map_file = NamedTempFile::new()?;
+            writeln!(
+                actual_map_file,
+                indoc! { "
+                    ..#^
+                    #^.~
+                    .~#.
+                    ~..#
+                " }
+            )?;
+            let expected_result = indoc! { "
+                Right 1, down 1: 5 (open 1, tree 2, rock 1, ice 0)
+                Right 3, down 1: 2 (open 2, tree 1, rock 0, ice 1)
+                Product: 10
+            " };
+
+            let mut command = Command::cargo_bin("day-3")?;
+            command
+                .arg(actual_map_file.path())
+                .arg("--tile")
+                .arg("^,rock,3")
+                .arg("--tile")
+                .arg("~,ice,1")
+                .arg("--slope")
+                .arg("1,1")
+                .arg("--slope")
+                .arg("3,1");
+
+            command
+                .assert()
+                .success()
+                .stdout(predicate::eq(expected_result));
+            Ok(())
+        }
+
+        #[test]
+        fn then_we_are_told_about_malformed_tiles() -> Result<(), Box<dyn std::error::Error>> {
+            let mut actual_map_file = NamedTempFile::new()?;
+            writeln!(actual_map_file, "..#^")?;
+
+            let mut command = Command::cargo_bin("day-3")?;
+            command
+                .arg(actual_map_file.path())
+                .arg("--tile")
+                .arg("^,rock");
+
+            command.assert().failure().stderr(predicate::str::contains(
+                "tile was expected to look like \"SYMBOL,NAME,WEIGHT\"",
+            ));
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod given_map_with_other_characters {
+    use super::*;
+
+    mod when_sliding_down_map {
+        use super::*;
+
+        #[test]
+        fn then_we_are_told_where_the_first_bad_character_is(
+        ) -> Result<(), Box<dyn std::error::Error>> {
+            let mut actual_map_file = NamedTempFile::new()?;
+            writeln!(
+                actual_map_file,
+                indoc! { "
+                    ..##.......
+                    #...#..X#..
+                    .#....#..#.
+                " }
+            )?;
+
+            let mut command = Command::cargo_bin("day-3")?;
+            command.arg(actual_map_file.path());
+
+            command
+                .assert()
+                .failure()
+                .stderr(predicate::str::contains("row 2 had 'X' at column 8"));
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod given_empty_map {
+    use super::*;
+
+    mod when_sliding_down_map {
+        use super::*;
+
+        #[test]
+        fn then_we_are_told_the_map_is_empty() -> Result<(), Box<dyn std::error::Error>> {
+            let actual_map_file = NamedTempFile::new()?;
+
+            let mut command = Command::cargo_bin("day-3")?;
+            command.arg(actual_map_file.path());
+
+            command
+                .assert()
+                .failure()
+                .stderr(predicate::str::contains("it was empty"));
+            Ok(())
+        }
+    }
+}